@@ -159,11 +159,12 @@ where
     }
     /// API function known as ngSpice_Command in Ngspice User's Manual
     /// If cmdstr is an empty string, NULL is sent to ngSpice_Command, which clears the internal control structures.
+    /// Returns true if ngspice reported an error.
     pub fn command(&self, cmdstr: &str) -> bool {
         let ret = if cmdstr.is_empty() {
             (self.api.command)(std::ptr::null())
         }
-        // have users spawn their own threads instead
+        // background commands are sent with command_bg
         else if cmdstr.find("bg_") == Some(0) {
             0
         } else {
@@ -172,12 +173,26 @@ where
         };
         ret != 0
     }
+    /// API function known as ngSpice_Command, sending cmdstr prefixed with `bg_` so that ngspice runs it in its own background thread,
+    /// e.g. `run`, `halt`, `resume` or an analysis such as `tran 1u 1m`. The state of the background thread is reported through cb_bgt_state.
+    /// Returns true if ngspice reported an error.
+    pub fn command_bg(&self, cmdstr: &str) -> bool {
+        let ccmdstr = std::ffi::CString::new(format!("bg_{}", cmdstr)).unwrap();
+        (self.api.command)(ccmdstr.as_ptr()) != 0
+    }
 
-    pub fn get_vec_info(&self, vecname: &str) -> PkVectorinfo {
+    /// API function known as ngGet_Vec_Info in Ngspice User's Manual
+    /// vecname may be given as `vectorname` (current plot) or `plotname.vectorname`.
+    /// Returns None if the vector does not exist.
+    pub fn get_vec_info(&self, vecname: &str) -> Option<PkVectorinfo> {
         unsafe {
             let cvecname = std::ffi::CString::new(vecname).unwrap();
             let pvectorinfo = (self.api.get_vec_info)(cvecname.as_ptr());
-            (*pvectorinfo).to_pk()
+            if pvectorinfo.is_null() {
+                None
+            } else {
+                Some((*pvectorinfo).to_pk())
+            }
         }
     }
 
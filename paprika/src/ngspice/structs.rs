@@ -21,6 +21,11 @@ pub struct NgComplex {
     cx_real: c_double,
     cx_imag: c_double,
 }
+/// vector flag denoting real data, known as VF_REAL in ngspice
+const VF_REAL: c_short = 1 << 0;
+/// vector flag denoting complex data, known as VF_COMPLEX in ngspice
+const VF_COMPLEX: c_short = 1 << 1;
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct NgVectorinfo {
@@ -33,8 +38,9 @@ pub struct NgVectorinfo {
 }
 impl NgVectorinfo {
     pub unsafe fn to_pk(self) -> PkVectorinfo {
-        let (real, comp) = match self.v_type {
-            1 => {
+        // v_type is the physical type of the vector (voltage, time, etc.) - data format is given by flags
+        let (real, comp) = match self.v_flag & (VF_REAL | VF_COMPLEX) {
+            VF_REAL => {
                 // real
                 let cvec = std::slice::from_raw_parts(self.v_realdata, self.v_length as usize);
                 // create vec containing 'count' number of PkVecvalues
@@ -46,7 +52,7 @@ impl NgVectorinfo {
                 }
                 (Some(vec), None)
             } // real
-            2 => {
+            VF_COMPLEX => {
                 // complex
                 let cvec = std::slice::from_raw_parts(self.v_compdata, self.v_length as usize);
                 // create vec containing 'count' number of PkVecvalues
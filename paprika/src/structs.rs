@@ -67,3 +67,26 @@ pub struct PkVectorinfo {
     pub compdata: Option<Vec<num::Complex<f64>>>,
     pub length: i32,
 }
+
+impl PkVectorinfo {
+    /// returns the vector data as complex numbers, whether it is stored as real or complex data
+    pub fn data_complex(&self) -> Vec<num::Complex<f64>> {
+        if let Some(v) = &self.compdata {
+            v.clone()
+        } else if let Some(v) = &self.realdata {
+            v.iter().map(|x| num::Complex::new(*x, 0.0)).collect()
+        } else {
+            vec![]
+        }
+    }
+    /// returns the real part of the vector data, whether it is stored as real or complex data
+    pub fn data_real(&self) -> Vec<f64> {
+        if let Some(v) = &self.realdata {
+            v.clone()
+        } else if let Some(v) = &self.compdata {
+            v.iter().map(|x| x.re).collect()
+        } else {
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! measure
+//! automatic measurements of a trace, such as rms value or rise time
+
+use std::fmt::Display;
+
+use crate::analysis::trace::Trace;
+use crate::schematic::eng;
+use crate::transforms::VSPoint;
+
+/// fraction of the step height within which a trace is considered settled
+const SETTLING_BAND: f32 = 0.02;
+
+/// a measurement of a trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measurement {
+    /// lowest value
+    Min,
+    /// highest value
+    Max,
+    /// mean value over x
+    Average,
+    /// root mean square value over x
+    Rms,
+    /// difference between highest and lowest value
+    PeakToPeak,
+    /// from 10% to 90% of the peak to peak range, on the first rising edge
+    RiseTime,
+    /// from 90% to 10% of the peak to peak range, on the first falling edge
+    FallTime,
+    /// inverse of the mean period between rising crossings of the midpoint
+    Frequency,
+    /// how far the peak goes beyond the final value, relative to the step from the initial value
+    Overshoot,
+    /// from the start until the trace stays within 2% of the step of its final value
+    SettlingTime,
+}
+
+impl Measurement {
+    /// all variants, in the order they are listed
+    pub const ALL: [Measurement; 10] = [
+        Measurement::Min,
+        Measurement::Max,
+        Measurement::Average,
+        Measurement::Rms,
+        Measurement::PeakToPeak,
+        Measurement::RiseTime,
+        Measurement::FallTime,
+        Measurement::Frequency,
+        Measurement::Overshoot,
+        Measurement::SettlingTime,
+    ];
+    /// returns the unit of the measurement of trace
+    fn unit(&self, trace: &Trace) -> String {
+        match self {
+            Measurement::Min
+            | Measurement::Max
+            | Measurement::Average
+            | Measurement::Rms
+            | Measurement::PeakToPeak => trace.unit.clone(),
+            Measurement::RiseTime | Measurement::FallTime | Measurement::SettlingTime => {
+                trace.x_unit.clone()
+            }
+            Measurement::Frequency => match trace.x_unit.as_str() {
+                "s" => String::from("Hz"),
+                "" => String::new(),
+                u => format!("/{}", u),
+            },
+            Measurement::Overshoot => String::from("%"),
+        }
+    }
+    /// returns the measurement of points, which are in order of x. None if it is undefined, e.g. the frequency of a dc level
+    pub fn measure(&self, points: &[VSPoint]) -> Option<f32> {
+        if points.len() < 2 {
+            return None;
+        }
+        let min = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max = points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+        let span = points.last()?.x - points.first()?.x;
+        let (first, last) = (points.first()?.y, points.last()?.y);
+        let lo = min + 0.1 * (max - min);
+        let hi = min + 0.9 * (max - min);
+        match self {
+            Measurement::Min => Some(min),
+            Measurement::Max => Some(max),
+            Measurement::Average => (span > 0.0).then(|| integral(points, |y| y) / span),
+            Measurement::Rms => (span > 0.0).then(|| (integral(points, |y| y * y) / span).sqrt()),
+            Measurement::PeakToPeak => Some(max - min),
+            Measurement::RiseTime => {
+                let start = *crossings(points, lo, true).first()?;
+                let end = crossings(points, hi, true)
+                    .into_iter()
+                    .find(|x| *x >= start)?;
+                Some(end - start)
+            }
+            Measurement::FallTime => {
+                let start = *crossings(points, hi, false).first()?;
+                let end = crossings(points, lo, false)
+                    .into_iter()
+                    .find(|x| *x >= start)?;
+                Some(end - start)
+            }
+            Measurement::Frequency => {
+                let rising = crossings(points, (min + max) / 2.0, true);
+                let periods = rising.len().checked_sub(1)?;
+                let duration = rising.last()? - rising.first()?;
+                (periods > 0 && duration > 0.0).then(|| periods as f32 / duration)
+            }
+            Measurement::Overshoot => {
+                let step = last - first;
+                let peak = if step > 0.0 { max } else { min };
+                (step != 0.0).then(|| (peak - last) / step * 100.0)
+            }
+            Measurement::SettlingTime => {
+                let step = last - first;
+                if step == 0.0 {
+                    return None;
+                }
+                let band = SETTLING_BAND * step.abs();
+                // the first point after the last one outside of the band
+                let settled = points
+                    .iter()
+                    .rposition(|p| (p.y - last).abs() > band)
+                    .map_or(0, |i| i + 1);
+                Some(points[settled.min(points.len() - 1)].x - points[0].x)
+            }
+        }
+    }
+    /// returns the measurement of points of trace in engineering notation with unit, or `-` if it is undefined
+    pub fn formatted(&self, trace: &Trace, points: &[VSPoint]) -> String {
+        match self.measure(points) {
+            Some(v) => format!("{}{}", eng::format(v as f64), self.unit(trace)),
+            None => String::from("-"),
+        }
+    }
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Measurement::Min => "min",
+                Measurement::Max => "max",
+                Measurement::Average => "average",
+                Measurement::Rms => "rms",
+                Measurement::PeakToPeak => "peak to peak",
+                Measurement::RiseTime => "rise time",
+                Measurement::FallTime => "fall time",
+                Measurement::Frequency => "frequency",
+                Measurement::Overshoot => "overshoot",
+                Measurement::SettlingTime => "settling time",
+            }
+        )
+    }
+}
+
+/// returns the integral of f(y) over x, by the trapezoidal rule
+fn integral(points: &[VSPoint], f: impl Fn(f32) -> f32) -> f32 {
+    points
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x) * (f(w[0].y) + f(w[1].y)) / 2.0)
+        .sum()
+}
+
+/// returns the x values at which points cross level, interpolated between samples, on rising or falling edges
+fn crossings(points: &[VSPoint], level: f32, rising: bool) -> Vec<f32> {
+    points
+        .windows(2)
+        .filter_map(|w| {
+            let (a, b) = (w[0], w[1]);
+            let crosses = if rising {
+                a.y < level && b.y >= level
+            } else {
+                a.y > level && b.y <= level
+            };
+            crosses.then(|| a.x + (b.x - a.x) * (level - a.y) / (b.y - a.y))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns points from (x, y) pairs
+    fn points(xy: &[(f32, f32)]) -> Vec<VSPoint> {
+        xy.iter().map(|(x, y)| VSPoint::new(*x, *y)).collect()
+    }
+
+    /// asserts that a measurement is defined and within 1e-4 of expected
+    fn assert_measures(m: Measurement, points: &[VSPoint], expected: f32) {
+        let v = m.measure(points).unwrap();
+        assert!((v - expected).abs() < 1e-4, "{}: {} != {}", m, v, expected);
+    }
+
+    #[test]
+    fn rise_and_fall_time() {
+        let pulse = points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (4.0, 0.0)]);
+        // 10% to 90% of a ramp over 1
+        assert_measures(Measurement::RiseTime, &pulse, 0.8);
+        // 90% to 10% of a ramp over 2
+        assert_measures(Measurement::FallTime, &pulse, 1.6);
+        let ramp = points(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(Measurement::FallTime.measure(&ramp), None);
+    }
+
+    #[test]
+    fn frequency_of_triangle() {
+        // triangle wave of period 1 over 3 periods
+        let triangle: Vec<VSPoint> = (0..=12)
+            .map(|i| VSPoint::new(i as f32 * 0.25, [0.0, 1.0, 0.0, -1.0][i % 4]))
+            .collect();
+        assert_measures(Measurement::Frequency, &triangle, 1.0);
+        let dc = points(&[(0.0, 1.0), (1.0, 1.0)]);
+        assert_eq!(Measurement::Frequency.measure(&dc), None);
+    }
+
+    #[test]
+    fn overshoot_and_settling_time() {
+        let step = points(&[(0.0, 0.0), (1.0, 1.5), (2.0, 0.9), (3.0, 1.01), (4.0, 1.0)]);
+        assert_measures(Measurement::Overshoot, &step, 50.0);
+        // within 2% of the step from x = 3 on
+        assert_measures(Measurement::SettlingTime, &step, 3.0);
+        let flat = points(&[(0.0, 1.0), (1.0, 1.0)]);
+        assert_eq!(Measurement::SettlingTime.measure(&flat), None);
+    }
+
+    #[test]
+    fn average_and_rms() {
+        let square = points(&[(0.0, 1.0), (1.0, 1.0), (1.0, -1.0), (2.0, -1.0)]);
+        assert_measures(Measurement::Average, &square, 0.0);
+        assert_measures(Measurement::Rms, &square, 1.0);
+        assert_measures(Measurement::PeakToPeak, &square, 2.0);
+    }
+}
@@ -1,3 +1,7 @@
+mod measure;
 mod plot;
 pub(crate) mod plot_page;
+mod spectrum;
+pub(crate) mod trace;
 mod viewport;
+mod waveform_math;
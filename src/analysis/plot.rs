@@ -1,11 +1,15 @@
 //! plot
 //! Space in which math values form plots
 
+use crate::analysis::measure::Measurement;
+use crate::analysis::trace::{Trace, TraceSource};
 use crate::analysis::viewport;
+use crate::analysis::waveform_math;
+use crate::schematic::eng;
 use crate::transforms::CSVec;
-use crate::transforms::{CSPoint, Point, VCTransform, VSBox, VSPoint};
+use crate::transforms::{CSBox, CSPoint, Point, VCTransform, VSBox, VSPoint, VSVec};
 use crate::Drawable;
-use iced::widget::canvas::{stroke, Path};
+use iced::widget::canvas::{stroke, Path, Text};
 use iced::{
     mouse,
     widget::canvas::{self, event::Event, path::Builder, Frame, LineCap, Stroke},
@@ -14,30 +18,78 @@ use iced::{
 use std::collections::HashSet;
 use std::default::Default;
 use std::hash::Hash;
+use std::rc::Rc;
 
 pub trait PlotElement: Hash + Eq + Drawable + Clone {
     fn bounding_box(&self) -> VSBox;
+    /// returns true if the element passes within tol of vsp, along each axis
+    fn near_vsp(&self, vsp: VSPoint, tol: VSVec) -> bool;
+    /// returns the index of the legend entry of the element
+    fn legend_index(&self) -> usize;
+    /// returns the name of the element, with its unit and source
+    fn label(&self) -> String;
 }
 
-pub type PlotTrace = Vec<VSPoint>;
+/// color of the default element
+const TRACE_COLOR: Color = Color::from_rgb(0.8, 0.8, 0.8);
+/// colors of the legend entries, repeated if there are more entries than colors
+const LEGEND_PALETTE: [Color; 8] = [
+    Color::from_rgb(1.0, 0.4, 0.4),
+    Color::from_rgb(0.4, 0.8, 1.0),
+    Color::from_rgb(0.5, 1.0, 0.5),
+    Color::from_rgb(1.0, 0.8, 0.3),
+    Color::from_rgb(0.8, 0.5, 1.0),
+    Color::from_rgb(0.3, 1.0, 0.9),
+    Color::from_rgb(1.0, 0.5, 0.8),
+    Color::from_rgb(0.7, 0.7, 0.4),
+];
+/// approximate spacing of gridlines, in canvas pixels
+const GRID_SPACING: f32 = 80.0;
+/// colors of cursors A and B
+const CURSOR_COLORS: [Color; 2] = [
+    Color::from_rgb(1.0, 0.6, 0.2),
+    Color::from_rgb(0.3, 0.9, 1.0),
+];
+/// names of the cursors
+const CURSOR_NAMES: [&str; 2] = ["A", "B"];
+
+/// an entry of the plot legend
+#[derive(Debug, Clone)]
+pub struct LegendEntry {
+    /// name of the entry
+    pub name: String,
+    /// color of the traces of the entry
+    pub color: Color,
+    /// false if the traces of the entry are hidden
+    pub visible: bool,
+}
 
 /// an enum to unify different types in schematic (nets and devices)
 #[derive(Debug, Clone)]
 pub enum ChartElement {
-    PlotTrace(PlotTrace),
+    /// a trace, with the index of its legend entry
+    PlotTrace(Rc<Trace>, usize),
 }
 
 impl Default for ChartElement {
     fn default() -> Self {
-        ChartElement::PlotTrace(Vec::from([VSPoint::origin(), VSPoint::new(1.0, 1.0)]))
+        let mut trace = Trace::new(
+            "",
+            "",
+            "",
+            TraceSource::Tran,
+            Vec::from([VSPoint::origin(), VSPoint::new(1.0, 1.0)]),
+        );
+        trace.color = Some(TRACE_COLOR);
+        ChartElement::PlotTrace(Rc::new(trace), 0)
     }
 }
 
 impl PartialEq for ChartElement {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::PlotTrace(l0), Self::PlotTrace(r0)) => {
-                by_address::ByAddress(l0) == by_address::ByAddress(r0)
+            (Self::PlotTrace(l0, _), Self::PlotTrace(r0, _)) => {
+                by_address::ByAddress(l0.clone()) == by_address::ByAddress(r0.clone())
             }
         }
     }
@@ -48,62 +100,59 @@ impl Eq for ChartElement {}
 impl std::hash::Hash for ChartElement {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
-            ChartElement::PlotTrace(d) => by_address::ByAddress(d).hash(state),
+            ChartElement::PlotTrace(d, _) => by_address::ByAddress(d.clone()).hash(state),
         }
     }
 }
 
+/// strokes the points of trace in color
+fn stroke_trace(trace: &Trace, vct: VCTransform, frame: &mut Frame, color: Color, width: f32) {
+    let stroke = Stroke {
+        width,
+        style: stroke::Style::Solid(color),
+        line_cap: LineCap::Square,
+        ..Stroke::default()
+    };
+    let mut path_builder = Builder::new();
+    for vsp in &trace.points {
+        path_builder.line_to(Point::from(vct.transform_point(*vsp)).into());
+    }
+    frame.stroke(&path_builder.build(), stroke);
+}
+
 impl Drawable for ChartElement {
     fn draw_persistent(&self, vct: VCTransform, _vcscale: f32, frame: &mut Frame) {
         match self {
-            ChartElement::PlotTrace(trace) => {
-                let stroke = Stroke {
-                    width: 1.0,
-                    style: stroke::Style::Solid(Color::from_rgb(0.8, 0.8, 0.8)),
-                    line_cap: LineCap::Square,
-                    ..Stroke::default()
-                };
-                let mut path_builder = Builder::new();
-                for vsp in trace {
-                    path_builder.line_to(Point::from(vct.transform_point(*vsp)).into());
-                }
-                frame.stroke(&path_builder.build(), stroke.clone());
+            ChartElement::PlotTrace(trace, _) => {
+                stroke_trace(trace, vct, frame, trace.color.unwrap_or(TRACE_COLOR), 1.0);
             }
         }
     }
 
     fn draw_selected(&self, vct: VCTransform, _vcscale: f32, frame: &mut Frame) {
         match self {
-            ChartElement::PlotTrace(trace) => {
-                let stroke = Stroke {
-                    width: 1.0,
-                    style: stroke::Style::Solid(Color::from_rgb(0.9, 0.9, 0.9)),
-                    line_cap: LineCap::Square,
-                    ..Stroke::default()
-                };
-                let mut path_builder = Builder::new();
-                for vsp in trace {
-                    path_builder.line_to(Point::from(vct.transform_point(*vsp)).into());
+            ChartElement::PlotTrace(trace, _) => {
+                let color = trace.color.unwrap_or(TRACE_COLOR);
+                stroke_trace(trace, vct, frame, color, 3.0);
+                // name the trace at its end
+                if let Some(last) = trace.points.last() {
+                    let csp = vct.transform_point(*last);
+                    frame.fill_text(Text {
+                        content: trace.label(),
+                        position: iced::Point::new(csp.x + 4.0, csp.y - 16.0),
+                        color,
+                        size: 14.0,
+                        ..Default::default()
+                    });
                 }
-                frame.stroke(&path_builder.build(), stroke.clone());
             }
         }
     }
 
     fn draw_preview(&self, vct: VCTransform, _vcscale: f32, frame: &mut Frame) {
         match self {
-            ChartElement::PlotTrace(trace) => {
-                let stroke = Stroke {
-                    width: 1.0,
-                    style: stroke::Style::Solid(Color::from_rgb(1.0, 1.0, 1.0)),
-                    line_cap: LineCap::Square,
-                    ..Stroke::default()
-                };
-                let mut path_builder = Builder::new();
-                for vsp in trace {
-                    path_builder.line_to(Point::from(vct.transform_point(*vsp)).into());
-                }
-                frame.stroke(&path_builder.build(), stroke.clone());
+            ChartElement::PlotTrace(trace, _) => {
+                stroke_trace(trace, vct, frame, Color::from_rgb(1.0, 1.0, 1.0), 1.0);
             }
         }
     }
@@ -112,7 +161,36 @@ impl Drawable for ChartElement {
 impl PlotElement for ChartElement {
     fn bounding_box(&self) -> VSBox {
         match self {
-            ChartElement::PlotTrace(trace) => VSBox::from_points(trace),
+            ChartElement::PlotTrace(trace, _) => VSBox::from_points(&trace.points),
+        }
+    }
+    fn near_vsp(&self, vsp: VSPoint, tol: VSVec) -> bool {
+        match self {
+            ChartElement::PlotTrace(trace, _) => {
+                // distance in units of tol, so that both axes count alike
+                let scale = |p: VSPoint| VSPoint::new(p.x / tol.x, p.y / tol.y);
+                let p = scale(vsp);
+                trace.points.windows(2).any(|w| {
+                    let (a, b) = (scale(w[0]), scale(w[1]));
+                    let ab = b - a;
+                    let t = if ab.square_length() > 0.0 {
+                        ((p - a).dot(ab) / ab.square_length()).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    (a + ab * t - p).length() <= 1.0
+                })
+            }
+        }
+    }
+    fn legend_index(&self) -> usize {
+        match self {
+            ChartElement::PlotTrace(_, i) => *i,
+        }
+    }
+    fn label(&self) -> String {
+        match self {
+            ChartElement::PlotTrace(trace, _) => trace.label(),
         }
     }
 }
@@ -129,8 +207,20 @@ pub trait ContentMsg {
 pub enum Msg {
     /// do nothing
     None,
-    /// new trace data
-    Traces(Vec<Vec<VSPoint>>),
+    /// new traces, along with legend entries naming consecutive groups of traces.
+    /// Each legend entry is a name and the number of traces in its group. Traces after the groups are named by themselves
+    Traces(Vec<Trace>, Vec<(String, usize)>),
+    /// shows or hides the traces of a legend entry
+    LegendVisible(usize, bool),
+    /// sets logarithmic scaling of the x and y axes
+    LogScale(bool, bool),
+    /// shows or hides the cursors
+    Cursors(bool),
+    /// sets the waveform math expressions, each plotted as a derived trace
+    Math(Vec<String>),
+    /// sets the traces the math expressions are evaluated over, e.g. those of every pane of a page.
+    /// Takes effect with the next traces or math expressions
+    MathSources(Vec<Trace>),
     /// iced canvas event, along with cursor position inside canvas bounds
     Event(Event, VSPoint),
 }
@@ -155,6 +245,8 @@ pub enum PlotSt {
     Idle,
     /// left click-drag area selection
     AreaSelect(VSBox),
+    /// left click-drag of cursor A (0) or B (1)
+    CursorDrag(usize),
 }
 
 /// struct holding schematic state (nets, devices, and their locations)
@@ -177,6 +269,28 @@ where
     tentatives: HashSet<E>,
     /// cursor position in schematic space
     curpos_vsp: VSPoint,
+    /// legend entries
+    legend: Vec<LegendEntry>,
+    /// traces as received, with the index of their legend entry. Content holds them as scaled for display
+    traces: Vec<(Trace, usize)>,
+    /// plot log10 of x values
+    log_x: bool,
+    /// plot log10 of y values
+    log_y: bool,
+    /// the content traces, as scaled for display, in the order of traces
+    shown: Vec<Rc<Trace>>,
+    /// x values of cursors A and B, unscaled
+    cursors: [Option<f32>; 2],
+    /// waveform math expressions
+    math: Vec<String>,
+    /// error of each math expression, if it could not be evaluated
+    math_errors: Vec<Option<String>>,
+    /// traces the math expressions are evaluated over. If empty, the received traces are used
+    math_sources: Vec<Trace>,
+    /// number of traces received from the simulation, derived traces follow them
+    received: usize,
+    /// number of legend entries of the received traces
+    received_legend: usize,
 }
 
 /// implement Schematic as viewport content
@@ -186,6 +300,7 @@ impl viewport::Content<Msg> for Plot<ChartElement> {
         match self.state {
             PlotSt::Idle => mouse::Interaction::default(),
             PlotSt::AreaSelect(_) => mouse::Interaction::Crosshair,
+            PlotSt::CursorDrag(_) => mouse::Interaction::ResizingHorizontally,
         }
     }
 
@@ -232,6 +347,8 @@ impl viewport::Content<Msg> for Plot<ChartElement> {
             .map(|e| e.draw_preview(vct, 1.0, frame))
             .collect();
 
+        self.draw_cursors(vct, frame);
+
         /// draw the cursor onto canvas
         pub fn draw_cursor(vct: VCTransform, frame: &mut Frame, curpos_vsp: VSPoint) {
             let cursor_stroke = || -> Stroke {
@@ -253,14 +370,18 @@ impl viewport::Content<Msg> for Plot<ChartElement> {
     }
     /// draw onto passive cache
     fn draw_passive(&self, vct: VCTransform, frame: &mut Frame) {
+        self.draw_axes(vct, frame);
         let _: Vec<_> = self
-            .selected
+            .content
             .iter()
-            .map(|e| e.draw_selected(vct, 1.0, frame))
+            .filter(|e| self.is_visible(e))
+            .map(|e| e.draw_persistent(vct, 1.0, frame))
             .collect();
+        // selected traces are drawn over the others
         let _: Vec<_> = self
-            .content
+            .selected
             .iter()
+            .filter(|e| self.is_visible(e))
             .map(|e| e.draw_selected(vct, 1.0, frame))
             .collect();
     }
@@ -283,32 +404,502 @@ impl viewport::Content<Msg> for Plot<ChartElement> {
         let mut clear_passive = false;
 
         match msg {
-            Msg::Event(event, curpos_vsp) => {
-                if let Event::Mouse(iced::mouse::Event::CursorMoved { .. }) = event {
+            Msg::Event(event, curpos_vsp) => match event {
+                Event::Mouse(iced::mouse::Event::CursorMoved { .. }) => {
                     self.update_cursor_vsp(curpos_vsp);
+                    if let PlotSt::CursorDrag(i) = self.state {
+                        self.cursors[i] = Some(self.snap_x(curpos_vsp.x));
+                    }
                 }
-            }
+                Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)) => {
+                    if let Some(i) = self.cursor_near(curpos_vsp) {
+                        self.state = PlotSt::CursorDrag(i);
+                    } else {
+                        // select the trace under the cursor, or clear the selection
+                        self.tentatives_to_selected();
+                        clear_passive = true;
+                    }
+                }
+                Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => {
+                    if let PlotSt::CursorDrag(_) = self.state {
+                        self.state = PlotSt::Idle;
+                    }
+                }
+                _ => {}
+            },
             Msg::None => {}
-            Msg::Traces(traces) => {
-                self.selected.clear();
-                self.tentatives.clear();
-                self.content.clear();
-
-                for trace in traces {
-                    self.content.insert(ChartElement::PlotTrace(trace));
+            Msg::LegendVisible(i, visible) => {
+                if let Some(entry) = self.legend.get_mut(i) {
+                    entry.visible = visible;
                 }
+                clear_passive = true;
+            }
+            Msg::Traces(traces, legend) => {
+                let groups = group_traces(traces, legend);
+                // every trace of a group has the color of its entry
+                self.legend = groups
+                    .iter()
+                    .map(|(name, traces)| LegendEntry {
+                        name: name.clone(),
+                        color: traces.first().and_then(|t| t.color).unwrap_or(TRACE_COLOR),
+                        visible: true,
+                    })
+                    .collect();
+                let traces: Vec<(Trace, usize)> = groups
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(i, (_, traces))| traces.into_iter().map(move |t| (t, i)))
+                    .collect();
+                // frequency is plotted on a log axis. Scaling is kept while the traces come from the same analyses, e.g. during a live run
+                let sources = |ts: &mut dyn Iterator<Item = TraceSource>| {
+                    let mut v: Vec<TraceSource> = vec![];
+                    ts.for_each(|s| {
+                        if !v.contains(&s) {
+                            v.push(s)
+                        }
+                    });
+                    v
+                };
+                let new_sources = sources(&mut traces.iter().map(|(t, _)| t.source));
+                if new_sources != sources(&mut self.traces.iter().map(|(t, _)| t.source)) {
+                    self.log_x = new_sources
+                        .iter()
+                        .any(|s| matches!(s, TraceSource::Ac | TraceSource::Noise));
+                    self.log_y = false;
+                }
+                self.traces = traces;
+                self.received = self.traces.len();
+                self.received_legend = self.legend.len();
+                self.apply_math();
+                self.rebuild();
 
                 clear_passive = true;
             }
+            Msg::LogScale(log_x, log_y) => {
+                self.log_x = log_x;
+                self.log_y = log_y;
+                self.rebuild();
+                clear_passive = true;
+            }
+            Msg::MathSources(traces) => {
+                self.math_sources = traces;
+            }
+            Msg::Math(exprs) => {
+                self.math = exprs;
+                self.apply_math();
+                self.rebuild();
+                clear_passive = true;
+            }
+            Msg::Cursors(show) => {
+                self.cursors = if show {
+                    // place the cursors at a third and two thirds of the plot
+                    let vsb = self.bounds();
+                    [1.0, 2.0].map(|k| Some(self.unscale_x(vsb.min.x + vsb.width() * k / 3.0)))
+                } else {
+                    [None; 2]
+                };
+                self.state = PlotSt::Idle;
+            }
         }
         clear_passive
     }
 }
 
+impl Plot<ChartElement> {
+    /// replaces the content with the traces, scaled for the log axes. Points which cannot be shown on a log axis are left out
+    fn rebuild(&mut self) {
+        self.selected.clear();
+        self.tentatives.clear();
+        self.active_element = None;
+        self.content.clear();
+        self.shown.clear();
+        let scale = |v: f32, log: bool| if log { v.log10() } else { v };
+        for (trace, i) in &self.traces {
+            let mut scaled = trace.clone();
+            scaled.points = trace
+                .points
+                .iter()
+                .map(|p| VSPoint::new(scale(p.x, self.log_x), scale(p.y, self.log_y)))
+                .filter(|p| p.x.is_finite() && p.y.is_finite())
+                .collect();
+            let scaled = Rc::new(scaled);
+            self.shown.push(scaled.clone());
+            self.content.insert(ChartElement::PlotTrace(scaled, *i));
+        }
+    }
+    /// replaces the derived traces with those of the math expressions, evaluated over the math sources or else the received traces
+    fn apply_math(&mut self) {
+        self.traces.truncate(self.received);
+        self.legend.truncate(self.received_legend);
+        let received: Vec<Trace> = if self.math_sources.is_empty() {
+            self.traces.iter().map(|(t, _)| t.clone()).collect()
+        } else {
+            self.math_sources.clone()
+        };
+        self.math_errors.clear();
+        for expr in &self.math {
+            match waveform_math::eval(expr, &received) {
+                Ok(mut trace) => {
+                    let i = self.legend.len();
+                    let color = LEGEND_PALETTE[i % LEGEND_PALETTE.len()];
+                    trace.color = Some(color);
+                    self.legend.push(LegendEntry {
+                        name: trace.label(),
+                        color,
+                        visible: true,
+                    });
+                    self.traces.push((trace, i));
+                    self.math_errors.push(None);
+                }
+                Err(e) => self.math_errors.push(Some(e)),
+            }
+        }
+    }
+    /// returns the waveform math expressions, each with its error if it could not be evaluated
+    pub fn math(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.math
+            .iter()
+            .zip(self.math_errors.iter())
+            .map(|(expr, e)| (expr.as_str(), e.as_deref()))
+    }
+    /// returns the x value as plotted
+    fn scale_x(&self, x: f32) -> f32 {
+        if self.log_x {
+            x.log10()
+        } else {
+            x
+        }
+    }
+    /// returns the x value of a plotted x position
+    fn unscale_x(&self, x: f32) -> f32 {
+        if self.log_x {
+            10f32.powf(x)
+        } else {
+            x
+        }
+    }
+    /// returns the y value as plotted
+    fn scale_y(&self, y: f32) -> f32 {
+        if self.log_y {
+            y.log10()
+        } else {
+            y
+        }
+    }
+    /// returns the received traces whose legend entries are visible
+    fn visible_traces(&self) -> impl Iterator<Item = &Trace> {
+        self.traces
+            .iter()
+            .filter(move |(_, i)| self.legend.get(*i).is_none_or(|entry| entry.visible))
+            .map(|(t, _)| t)
+    }
+    /// returns the received trace of a content element
+    fn original(&self, e: &ChartElement) -> Option<&Trace> {
+        let ChartElement::PlotTrace(shown, _) = e;
+        self.shown
+            .iter()
+            .position(|s| Rc::ptr_eq(s, shown))
+            .map(|k| &self.traces[k].0)
+    }
+    /// returns the index of the cursor near the plotted position vsp, if any
+    fn cursor_near(&self, vsp: VSPoint) -> Option<usize> {
+        let tol = self.tolerance().x;
+        (0..2).find(|i| self.cursors[*i].is_some_and(|x| (self.scale_x(x) - vsp.x).abs() <= tol))
+    }
+    /// returns the x value of the plotted position x, snapped to the nearest sample of a visible trace if one is close
+    fn snap_x(&self, x: f32) -> f32 {
+        let tol = self.tolerance().x / 2.0;
+        let nearest = self
+            .visible_traces()
+            .flat_map(|t| t.points.iter().map(|p| p.x))
+            .min_by(|a, b| {
+                (self.scale_x(*a) - x)
+                    .abs()
+                    .total_cmp(&(self.scale_x(*b) - x).abs())
+            });
+        match nearest {
+            Some(sample) if (self.scale_x(sample) - x).abs() <= tol => sample,
+            _ => self.unscale_x(x),
+        }
+    }
+    /// draws the cursors as vertical lines, marking where they cross the visible traces
+    fn draw_cursors(&self, vct: VCTransform, frame: &mut Frame) {
+        for ((x, color), name) in self.cursors.iter().zip(CURSOR_COLORS).zip(CURSOR_NAMES) {
+            let Some(x) = x else {
+                continue;
+            };
+            let csx = vct.transform_point(VSPoint::new(self.scale_x(*x), 0.0)).x;
+            frame.stroke(
+                &Path::line(
+                    iced::Point::new(csx, 0.0),
+                    iced::Point::new(csx, frame.height()),
+                ),
+                Stroke {
+                    width: 1.0,
+                    style: stroke::Style::Solid(color),
+                    ..Stroke::default()
+                },
+            );
+            frame.fill_text(Text {
+                content: name.to_string(),
+                position: iced::Point::new(csx + 3.0, 2.0),
+                color,
+                size: 14.0,
+                ..Default::default()
+            });
+            for y in self.visible_traces().filter_map(|t| t.value_at(*x)) {
+                let csp = vct.transform_point(VSPoint::new(self.scale_x(*x), self.scale_y(y)));
+                frame.stroke(
+                    &Path::circle(Point::from(csp).into(), 3.0),
+                    Stroke {
+                        width: 1.0,
+                        style: stroke::Style::Solid(color),
+                        ..Stroke::default()
+                    },
+                );
+            }
+        }
+    }
+    /// returns true if the cursors are shown
+    pub fn cursors_shown(&self) -> bool {
+        self.cursors.iter().any(|c| c.is_some())
+    }
+    /// returns the cursor readout: the cursor positions and their difference, then the values of every visible trace at each cursor
+    pub fn cursor_readout(&self) -> Vec<String> {
+        if !self.cursors_shown() {
+            return vec![];
+        }
+        let x_unit = self.traces.first().map_or("", |(t, _)| t.x_unit.as_str());
+        let x_name = match x_unit {
+            "s" => "t",
+            "Hz" => "f",
+            _ => "x",
+        };
+        let mut lines: Vec<String> = self
+            .cursors
+            .iter()
+            .zip(CURSOR_NAMES)
+            .filter_map(|(x, name)| {
+                x.map(|x| format!("{}: {}={}{}", name, x_name, eng::format(x as f64), x_unit))
+            })
+            .collect();
+        if let [Some(a), Some(b)] = self.cursors {
+            let dx = (b - a) as f64;
+            let inverse_unit = match x_unit {
+                "s" => String::from("Hz"),
+                "" => String::new(),
+                u => format!("/{}", u),
+            };
+            lines.push(format!(
+                "Δ{}={}{}, 1/Δ{}={}{}",
+                x_name,
+                eng::format(dx),
+                x_unit,
+                x_name,
+                if dx == 0.0 {
+                    String::from("-")
+                } else {
+                    eng::format(1.0 / dx)
+                },
+                inverse_unit
+            ));
+        }
+        for t in self.visible_traces() {
+            let values: Vec<Option<f32>> = self
+                .cursors
+                .iter()
+                .map(|x| x.and_then(|x| t.value_at(x)))
+                .collect();
+            let mut line = t.name.clone();
+            for (v, name) in values.iter().zip(CURSOR_NAMES) {
+                if let Some(v) = v {
+                    line.push_str(&format!(" {}={}{}", name, eng::format(*v as f64), t.unit));
+                }
+            }
+            if let [Some(a), Some(b)] = values[..] {
+                line.push_str(&format!(
+                    " Δ{}={}{}",
+                    t.unit,
+                    eng::format((b - a) as f64),
+                    t.unit
+                ));
+            }
+            lines.push(line);
+        }
+        lines
+    }
+    /// returns the name of the legend entry of the selected trace, unless it is a derived trace
+    pub fn selected_group(&self) -> Option<&str> {
+        let i = self.active_element.as_ref()?.legend_index();
+        (i < self.received_legend).then(|| self.legend[i].name.as_str())
+    }
+    /// returns the selected trace, as received
+    pub fn selected_trace(&self) -> Option<&Trace> {
+        self.original(self.active_element.as_ref()?)
+    }
+    /// returns the selected trace with its points between the cursors if both are placed, otherwise all of its points
+    pub fn selected_points(&self) -> Option<(&Trace, Vec<VSPoint>)> {
+        let trace = self.selected_trace()?;
+        let points = match self.cursors {
+            [Some(a), Some(b)] => trace.window(a.min(b), a.max(b)),
+            _ => trace.points.clone(),
+        };
+        Some((trace, points))
+    }
+    /// returns the name of the selected trace with its measurements, taken between the cursors if both are placed
+    pub fn measurements(&self) -> Option<(String, Vec<(Measurement, String)>)> {
+        let (trace, points) = self.selected_points()?;
+        Some((
+            trace.label(),
+            Measurement::ALL
+                .iter()
+                .map(|m| (*m, m.formatted(trace, &points)))
+                .collect(),
+        ))
+    }
+    /// returns the x and y units of the traces, distinct units separated by commas
+    fn units(&self) -> (String, String) {
+        let join = |units: Vec<&str>| {
+            let mut distinct: Vec<&str> = vec![];
+            for u in units {
+                if !u.is_empty() && !distinct.contains(&u) {
+                    distinct.push(u);
+                }
+            }
+            distinct.join(",")
+        };
+        (
+            join(self.traces.iter().map(|(t, _)| t.x_unit.as_str()).collect()),
+            join(self.traces.iter().map(|(t, _)| t.unit.as_str()).collect()),
+        )
+    }
+    /// draws gridlines and the axes along the left and bottom of the frame, with tick labels in engineering notation
+    fn draw_axes(&self, vct: VCTransform, frame: &mut Frame) {
+        let Some(cvt) = vct.inverse() else {
+            return;
+        };
+        let (w, h) = (frame.width(), frame.height());
+        let vsb =
+            cvt.outer_transformed_box(&CSBox::from_points([CSPoint::origin(), CSPoint::new(w, h)]));
+        let (x_unit, y_unit) = self.units();
+        let grid_stroke = Stroke {
+            width: 1.0,
+            style: stroke::Style::Solid(Color::from_rgba(1.0, 1.0, 1.0, 0.08)),
+            ..Stroke::default()
+        };
+        let axis_stroke = Stroke {
+            width: 1.0,
+            style: stroke::Style::Solid(Color::from_rgb(0.6, 0.6, 0.6)),
+            ..Stroke::default()
+        };
+        let label_color = Color::from_rgb(0.7, 0.7, 0.7);
+        for x in ticks(vsb.min.x, vsb.max.x, w / GRID_SPACING, self.log_x) {
+            let csx = vct.transform_point(VSPoint::new(x, vsb.min.y)).x;
+            frame.stroke(
+                &Path::line(iced::Point::new(csx, 0.0), iced::Point::new(csx, h)),
+                grid_stroke.clone(),
+            );
+            frame.fill_text(Text {
+                content: tick_label(x, self.log_x, &x_unit),
+                position: iced::Point::new(csx + 2.0, h - 16.0),
+                color: label_color,
+                size: 12.0,
+                ..Default::default()
+            });
+        }
+        for y in ticks(vsb.min.y, vsb.max.y, h / GRID_SPACING, self.log_y) {
+            let csy = vct.transform_point(VSPoint::new(vsb.min.x, y)).y;
+            frame.stroke(
+                &Path::line(iced::Point::new(0.0, csy), iced::Point::new(w, csy)),
+                grid_stroke.clone(),
+            );
+            frame.fill_text(Text {
+                content: tick_label(y, self.log_y, &y_unit),
+                position: iced::Point::new(4.0, csy - 14.0),
+                color: label_color,
+                size: 12.0,
+                ..Default::default()
+            });
+        }
+        frame.stroke(
+            &Path::line(iced::Point::new(0.0, h - 1.0), iced::Point::new(w, h - 1.0)),
+            axis_stroke.clone(),
+        );
+        frame.stroke(
+            &Path::line(iced::Point::new(1.0, 0.0), iced::Point::new(1.0, h)),
+            axis_stroke,
+        );
+    }
+    /// returns whether the x and y axes are logarithmic
+    pub fn log_scale(&self) -> (bool, bool) {
+        (self.log_x, self.log_y)
+    }
+}
+
+/// splits traces into the consecutive groups named by legend, each entry a name and the number of traces in its group.
+/// Traces after the groups form groups of their own, named by their label. Every trace takes the color of the first
+/// trace of its group, or else the palette color of the group, so that the plot matches the legend
+pub fn group_traces(traces: Vec<Trace>, legend: Vec<(String, usize)>) -> Vec<(String, Vec<Trace>)> {
+    let mut traces = traces.into_iter();
+    let mut groups: Vec<(String, Vec<Trace>)> = legend
+        .into_iter()
+        .map(|(name, count)| (name, traces.by_ref().take(count).collect()))
+        .collect();
+    groups.extend(traces.map(|t| (t.label(), vec![t])));
+    for (i, (_, traces)) in groups.iter_mut().enumerate() {
+        let color = traces
+            .first()
+            .and_then(|t| t.color)
+            .unwrap_or(LEGEND_PALETTE[i % LEGEND_PALETTE.len()]);
+        for t in traces.iter_mut() {
+            t.color = Some(color);
+        }
+    }
+    groups
+}
+
+/// returns the tick positions between min and max, about count of them at 1, 2 or 5 times a power of ten.
+/// On a log axis the values are exponents and ticks are at whole decades
+fn ticks(min: f32, max: f32, count: f32, log: bool) -> Vec<f32> {
+    let raw = (max - min) / count.max(1.0);
+    if !(raw > 0.0 && raw.is_finite()) {
+        return vec![];
+    }
+    let mag = 10f32.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * mag)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * mag);
+    let step = if log { step.max(1.0).round() } else { step };
+    let (first, last) = ((min / step).ceil() as i64, (max / step).floor() as i64);
+    (first..=last).map(|k| k as f32 * step).collect()
+}
+
+/// returns the label of the tick at v, in engineering notation with unit
+fn tick_label(v: f32, log: bool, unit: &str) -> String {
+    let v = if log { 10f64.powf(v as f64) } else { v as f64 };
+    format!("{}{}", eng::format(v), unit)
+}
+
 impl<E> Plot<E>
 where
     E: PlotElement,
 {
+    /// returns the legend entries
+    pub fn legend(&self) -> &[LegendEntry] {
+        &self.legend
+    }
+    /// returns true if the legend entry of e is visible
+    fn is_visible(&self, e: &E) -> bool {
+        self.legend
+            .get(e.legend_index())
+            .is_none_or(|entry| entry.visible)
+    }
+    /// returns the name of the active element, if any
+    pub fn active_label(&self) -> Option<String> {
+        self.active_element.as_ref().map(|e| e.label())
+    }
     fn update_cursor_vsp(&mut self, curpos_vsp: VSPoint) {
         self.curpos_vsp = curpos_vsp;
         self.tentative_by_vspoint(curpos_vsp, &mut self.selskip.clone());
@@ -344,24 +935,37 @@ where
     /// put every element with tentative flag set into selected vector
     fn tentatives_to_selected(&mut self) {
         self.selected = self.tentatives.clone();
-        if self.tentatives.len() == 1 {
-            let mut v: Vec<_> = self.tentatives.iter().collect();
-            self.active_element = v.pop().cloned();
-        }
+        self.active_element = if self.tentatives.len() == 1 {
+            self.tentatives.iter().next().cloned()
+        } else {
+            None
+        };
         self.tentatives.clear();
     }
+    /// returns the distance within which the cursor picks up an element, 1% of the extent of the plot along each axis
+    fn tolerance(&self) -> VSVec {
+        let vsb = VSBox::from_points(self.content.iter().flat_map(|e| {
+            let b = e.bounding_box();
+            [b.min, b.max]
+        }));
+        VSVec::new(
+            vsb.width().max(f32::EPSILON),
+            vsb.height().max(f32::EPSILON),
+        ) * 0.01
+    }
     /// set 1 tentative flag based on ssp and skip number. Returns the flagged element, if any.
-    fn selectable(&mut self, _vsp: VSPoint, skip: &mut usize) -> Option<E> {
-        loop {
-            let count = 0; // tracks the number of skipped elements
-                           // if let Some(e) = self.content.selectable(vsp, *skip, &mut count) {
-                           //     return Some(e);
-                           // }
-            if count == 0 {
-                *skip = 0;
-                return None;
-            }
-            *skip -= count;
+    fn selectable(&mut self, vsp: VSPoint, skip: &mut usize) -> Option<E> {
+        let tol = self.tolerance();
+        let near: Vec<&E> = self
+            .content
+            .iter()
+            .filter(|e| self.is_visible(e) && e.near_vsp(vsp, tol))
+            .collect();
+        if near.is_empty() {
+            *skip = 0;
+            return None;
         }
+        *skip %= near.len();
+        Some(near[*skip].clone())
     }
 }
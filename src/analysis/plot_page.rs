@@ -1,34 +1,112 @@
 //! Schematic GUI page
 //! includes paramter editor, toolbar, and the canvas itself
 
-use crate::analysis::plot::{ChartElement, Msg as PlotMsg, Plot};
-use crate::analysis::viewport::Content;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::analysis::measure::Measurement;
+use crate::analysis::plot::{group_traces, ChartElement, Msg as PlotMsg, Plot};
+use crate::analysis::spectrum::{Spectrum, Window};
+use crate::analysis::trace::{Trace, TraceSource};
 use crate::analysis::viewport::VCTransformFreeAspect;
 use crate::analysis::{plot, viewport};
-use crate::transforms::VSPoint;
 
 use crate::IcedStruct;
-use iced::widget::row;
-use iced::Element;
+use iced::widget::{button, checkbox, pick_list, row, scrollable, text, text_input, Column};
+use iced::{Color, Element, Length};
+
+/// pane a trace can be moved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneChoice {
+    /// an existing pane, by index from the top
+    Pane(usize),
+    /// a new pane below the others
+    New,
+}
+
+impl Display for PaneChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaneChoice::Pane(i) => write!(f, "pane {}", i + 1),
+            PaneChoice::New => write!(f, "new pane"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum PlotPageMsg {
-    ViewportEvt(viewport::CompositeMsg<plot::Msg>),
-    Traces(Vec<Vec<VSPoint>>),
+    /// event of the viewport of a pane
+    ViewportEvt(usize, viewport::CompositeMsg<plot::Msg>),
+    Traces(Vec<Trace>, Vec<(String, usize)>),
+    /// traces of a simulation still running, the view is extended to keep them in sight
+    LiveTraces(Vec<Trace>, Vec<(String, usize)>),
+    /// shows or hides the traces of a legend entry of a pane
+    LegendToggled(usize, usize, bool),
+    /// sets logarithmic scaling of the x and y axes
+    LogScaleToggled(bool, bool),
+    /// shows or hides the cursors
+    CursorsToggled(bool),
+    /// measures the selected trace
+    Measure,
+    /// waveform math expression edited
+    MathInputChanged(String),
+    /// adds the waveform math expression
+    MathSubmit,
+    /// removes a waveform math expression
+    MathRemove(usize),
+    /// fft window selected
+    WindowSelected(Window),
+    /// takes the fft of the selected transient trace
+    Fft,
+    /// spectrum traces, with the readout of their fundamental, thd and snr
+    Spectrum(Vec<Trace>, Vec<String>),
+    /// moves the legend entry of the selected trace to another pane
+    MoveToPane(PaneChoice),
+}
+
+type PlotViewport = viewport::Viewport<Plot<ChartElement>, plot::Msg>;
+
+/// returns an empty plot pane
+fn new_pane() -> PlotViewport {
+    let vct = VCTransformFreeAspect::identity()
+        .pre_flip_y()
+        .then_scale(10.0, 10.0);
+    viewport::Viewport::new(1.0, f32::EPSILON, f32::MAX, vct)
 }
 
 /// schematic
 pub struct PlotPage {
-    /// viewport
-    viewport: viewport::Viewport<Plot<ChartElement>, plot::Msg>,
+    /// plot panes stacked from top to bottom, sharing their x range
+    panes: Vec<PlotViewport>,
+    /// index of the pane last hovered, which the controls act on
+    active: usize,
+    /// the last received traces, grouped by legend entry
+    groups: Vec<(String, Vec<Trace>)>,
+    /// pane of each legend entry by name. Entries not listed are in the first pane
+    assignment: HashMap<String, usize>,
+    /// label of the last measured trace, with its measurements
+    measured: Option<(String, Vec<(Measurement, String)>)>,
+    /// waveform math expression being edited
+    math_input: String,
+    /// window of the fft
+    window: Window,
+    /// spectrum of the selected trace, to be shown in the spectrum tab
+    pub spectrum: Option<(Vec<Trace>, Vec<String>)>,
+    /// readout listed below the legend, e.g. the thd of a spectrum
+    notes: Vec<String>,
 }
 impl Default for PlotPage {
     fn default() -> Self {
-        let vct = VCTransformFreeAspect::identity()
-            .pre_flip_y()
-            .then_scale(10.0, 10.0);
         PlotPage {
-            viewport: viewport::Viewport::new(1.0, f32::EPSILON, f32::MAX, vct),
+            panes: vec![new_pane()],
+            active: 0,
+            groups: vec![],
+            assignment: HashMap::new(),
+            measured: None,
+            math_input: String::new(),
+            window: Window::default(),
+            spectrum: None,
+            notes: vec![],
         }
     }
 }
@@ -36,29 +114,180 @@ impl Default for PlotPage {
 impl IcedStruct<PlotPageMsg> for PlotPage {
     fn update(&mut self, msg: PlotPageMsg) {
         match msg {
-            PlotPageMsg::ViewportEvt(msgs) => {
-                self.viewport.update(msgs);
+            PlotPageMsg::ViewportEvt(i, msgs) => {
+                if i >= self.panes.len() {
+                    return;
+                }
+                self.active = i;
+                let new_view = matches!(msgs.viewport_msg, viewport::Msg::NewView(..));
+                self.panes[i].update(msgs);
+                // zooming or panning one pane moves the others along x
+                if new_view {
+                    let vct = self.panes[i].vct();
+                    for (j, pane) in self.panes.iter_mut().enumerate() {
+                        if j != i {
+                            pane.link_x(vct);
+                        }
+                    }
+                }
+            }
+            PlotPageMsg::Traces(traces, legend) => {
+                self.groups = group_traces(traces, legend);
+                self.distribute();
+                self.measured = None;
+            }
+            PlotPageMsg::LiveTraces(traces, legend) => {
+                self.groups = group_traces(traces, legend);
+                self.distribute();
+                for pane in self.panes.iter_mut() {
+                    pane.extend_to_content();
+                }
+                self.link_widest_x();
+            }
+            PlotPageMsg::LogScaleToggled(log_x, log_y) => {
+                // the x axis is shared by all panes, the y axis is the active pane's own
+                let active = self.active;
+                let x_changed = log_x != self.pane().content.log_scale().0;
+                for (j, pane) in self.panes.iter_mut().enumerate() {
+                    let log_y = if j == active {
+                        log_y
+                    } else {
+                        pane.content.log_scale().1
+                    };
+                    let y_changed = log_y != pane.content.log_scale().1;
+                    let vct = pane.vct();
+                    pane.update(viewport::CompositeMsg {
+                        content_msg: PlotMsg::LogScale(log_x, log_y),
+                        viewport_msg: viewport::Msg::None,
+                    });
+                    // the content is rescaled, e.g. x from 1..1e9 to 0..9, and would otherwise be left out of view
+                    if x_changed || y_changed {
+                        pane.fit_to_content();
+                        if !x_changed {
+                            pane.link_x(vct);
+                        }
+                    }
+                }
+                if x_changed {
+                    self.link_widest_x();
+                }
             }
-            PlotPageMsg::Traces(traces) => {
-                let content_msg = PlotMsg::Traces(traces);
-                self.viewport.content.update(content_msg);
+            PlotPageMsg::CursorsToggled(show) => {
+                self.pane_mut().update(viewport::CompositeMsg {
+                    content_msg: PlotMsg::Cursors(show),
+                    viewport_msg: viewport::Msg::None,
+                });
+            }
+            PlotPageMsg::MoveToPane(choice) => {
+                if let Some(name) = self.pane().content.selected_group().map(str::to_string) {
+                    let to = match choice {
+                        PaneChoice::Pane(i) => i,
+                        PaneChoice::New => self.panes.len(),
+                    };
+                    self.assignment.insert(name, to);
+                    self.distribute();
+                    self.measured = None;
+                }
+            }
+            PlotPageMsg::MathInputChanged(s) => {
+                self.math_input = s;
+            }
+            PlotPageMsg::MathSubmit => {
+                if !self.math_input.trim().is_empty() {
+                    let mut exprs: Vec<String> = self
+                        .pane()
+                        .content
+                        .math()
+                        .map(|(expr, _)| expr.to_string())
+                        .collect();
+                    exprs.push(std::mem::take(&mut self.math_input));
+                    self.set_math(exprs);
+                }
+            }
+            PlotPageMsg::MathRemove(i) => {
+                let exprs: Vec<String> = self
+                    .pane()
+                    .content
+                    .math()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, (expr, _))| expr.to_string())
+                    .collect();
+                self.set_math(exprs);
+            }
+            PlotPageMsg::WindowSelected(window) => {
+                self.window = window;
+            }
+            PlotPageMsg::Fft => {
+                let window = self.window;
+                let spectrum = self
+                    .pane()
+                    .content
+                    .selected_points()
+                    .and_then(|(trace, points)| {
+                        Spectrum::new(&points, window).map(|spectrum| {
+                            (
+                                spectrum.traces(&trace.name),
+                                spectrum.readout(window, &trace.unit),
+                            )
+                        })
+                    });
+                if spectrum.is_some() {
+                    self.spectrum = spectrum;
+                }
+            }
+            PlotPageMsg::Spectrum(traces, notes) => {
+                self.groups = group_traces(traces, vec![]);
+                self.distribute();
+                self.notes = notes;
+                self.measured = None;
+            }
+            PlotPageMsg::Measure => {
+                self.measured = self.pane().content.measurements();
+            }
+            PlotPageMsg::LegendToggled(p, i, visible) => {
+                let Some(pane) = self.panes.get_mut(p) else {
+                    return;
+                };
+                pane.update(viewport::CompositeMsg {
+                    content_msg: PlotMsg::LegendVisible(i, visible),
+                    viewport_msg: viewport::Msg::None,
+                });
             }
         }
     }
 
     fn view(&self) -> Element<PlotPageMsg> {
+        let pane = self.pane();
+        let (log_x, log_y) = pane.content.log_scale();
+        let unlog = |v: f32, log: bool| if log { 10f32.powf(v) } else { v };
         let str_ssp = format!(
             "curpos: x: {:.2e}; y: {:.2e}",
-            self.viewport.curpos_vsp().x,
-            self.viewport.curpos_vsp().y
+            unlog(pane.curpos_vsp().x, log_x),
+            unlog(pane.curpos_vsp().y, log_y)
         );
         let str_xyscales = format!(
             "scale: x: {:.2e}; y: {:.2e}",
-            self.viewport.vct().x_scale(),
-            self.viewport.vct().y_scale(),
+            pane.vct().x_scale(),
+            pane.vct().y_scale(),
         );
 
-        let canvas = self.viewport.view().map(PlotPageMsg::ViewportEvt);
+        let canvases: Vec<Element<PlotPageMsg>> = self
+            .panes
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p.view().map(move |m| PlotPageMsg::ViewportEvt(i, m)))
+            .collect();
+        let canvas: Element<PlotPageMsg> = Column::with_children(canvases)
+            .spacing(4)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into();
+        // measurements need a selected trace
+        let measure: Element<PlotPageMsg> = match pane.content.active_label() {
+            Some(_) => button("measure").on_press(PlotPageMsg::Measure).into(),
+            None => button("measure").into(),
+        };
         let infobar = row![
             iced::widget::text(str_ssp)
                 .size(16)
@@ -68,11 +297,214 @@ impl IcedStruct<PlotPageMsg> for PlotPage {
                 .size(16)
                 .height(16)
                 .vertical_alignment(iced::alignment::Vertical::Center),
+            checkbox("log x", log_x, move |b| PlotPageMsg::LogScaleToggled(
+                b, log_y
+            ))
+            .size(16),
+            checkbox("log y", log_y, move |b| PlotPageMsg::LogScaleToggled(
+                log_x, b
+            ))
+            .size(16),
+            checkbox(
+                "cursors",
+                pane.content.cursors_shown(),
+                PlotPageMsg::CursorsToggled
+            )
+            .size(16),
+            measure,
+            iced::widget::text(pane.content.active_label().unwrap_or_default())
+                .size(16)
+                .height(16)
+                .vertical_alignment(iced::alignment::Vertical::Center),
         ]
         .spacing(10);
 
-        let schematic = iced::widget::column![canvas, infobar,];
+        let main_area: Element<PlotPageMsg> =
+            if self.panes.iter().all(|p| p.content.legend().is_empty()) {
+                canvas
+            } else {
+                let mut entries: Vec<Element<PlotPageMsg>> = vec![];
+                for (p, pane) in self.panes.iter().enumerate() {
+                    if self.panes.len() > 1 {
+                        entries.push(text(PaneChoice::Pane(p)).size(14).into());
+                    }
+                    entries.extend(pane.content.legend().iter().enumerate().map(|(i, entry)| {
+                        row![
+                            checkbox("", entry.visible, move |b| PlotPageMsg::LegendToggled(
+                                p, i, b
+                            )),
+                            text(&entry.name).size(14).style(entry.color)
+                        ]
+                        .into()
+                    }));
+                }
+                // cursor readout and measurements are listed below the legend
+                let readout = pane.content.cursor_readout();
+                if !readout.is_empty() {
+                    entries.push(text("cursors").size(14).into());
+                    entries.extend(readout.into_iter().map(|l| text(l).size(12).into()));
+                }
+                entries.extend(self.notes.iter().map(|l| text(l).size(12).into()));
+                if let Some((label, measurements)) = &self.measured {
+                    entries.push(text(label).size(14).into());
+                    entries.extend(
+                        measurements
+                            .iter()
+                            .map(|(m, v)| text(format!("{}: {}", m, v)).size(12).into()),
+                    );
+                }
+                row![
+                    canvas,
+                    scrollable(Column::with_children(entries).spacing(2)).width(220)
+                ]
+                .into()
+            };
+
+        // the fft takes a transient trace
+        let fft: Element<PlotPageMsg> = match pane.content.selected_trace() {
+            Some(trace) if trace.source == TraceSource::Tran => {
+                button("fft").on_press(PlotPageMsg::Fft).into()
+            }
+            _ => button("fft").into(),
+        };
+        let mut math_bar = row![
+            pick_list(
+                &Window::ALL[..],
+                Some(self.window),
+                PlotPageMsg::WindowSelected
+            ),
+            fft,
+            text_input("math, e.g. db(v(out)/v(in))", &self.math_input)
+                .on_input(PlotPageMsg::MathInputChanged)
+                .on_submit(PlotPageMsg::MathSubmit)
+                .width(300)
+        ]
+        .spacing(10);
+        // the selected trace moves with its legend entry
+        if pane.content.selected_group().is_some() {
+            let choices: Vec<PaneChoice> = (0..self.panes.len())
+                .filter(|i| *i != self.active)
+                .map(PaneChoice::Pane)
+                .chain([PaneChoice::New])
+                .collect();
+            math_bar = math_bar
+                .push(pick_list(choices, None, PlotPageMsg::MoveToPane).placeholder("move to"));
+        }
+        for (i, (expr, e)) in pane.content.math().enumerate() {
+            math_bar = match e {
+                Some(e) => math_bar.push(
+                    text(format!("{}: {}", expr, e))
+                        .size(16)
+                        .style(Color::from_rgb(1.0, 0.4, 0.4)),
+                ),
+                None => math_bar.push(text(expr).size(16)),
+            };
+            math_bar = math_bar.push(button("x").on_press(PlotPageMsg::MathRemove(i)));
+        }
+
+        let schematic = iced::widget::column![main_area, math_bar, infobar,];
 
         schematic.into()
     }
 }
+
+impl PlotPage {
+    /// returns the active pane
+    fn pane(&self) -> &PlotViewport {
+        &self.panes[self.active]
+    }
+    /// returns the active pane, mutably
+    fn pane_mut(&mut self) -> &mut PlotViewport {
+        &mut self.panes[self.active]
+    }
+    /// returns the pane index of the legend entry name
+    fn pane_of(&self, name: &str) -> usize {
+        self.assignment.get(name).copied().unwrap_or(0)
+    }
+    /// hands each pane the groups assigned to it. Panes left without traces are removed, though the first pane is always kept
+    fn distribute(&mut self) {
+        let mut used: Vec<usize> = self
+            .groups
+            .iter()
+            .map(|(name, _)| self.pane_of(name))
+            .collect();
+        used.push(0);
+        used.sort_unstable();
+        used.dedup();
+        // renumber the panes in use from the top
+        self.assignment.retain(|_, p| used.contains(p));
+        for p in self.assignment.values_mut() {
+            *p = used.iter().position(|u| *u == *p).unwrap_or(0);
+        }
+        self.active = used.iter().position(|u| *u == self.active).unwrap_or(0);
+        let mut old: Vec<Option<PlotViewport>> = std::mem::take(&mut self.panes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut added = vec![];
+        for (i, u) in used.iter().enumerate() {
+            match old.get_mut(*u).and_then(|p| p.take()) {
+                Some(pane) => self.panes.push(pane),
+                None => {
+                    self.panes.push(new_pane());
+                    added.push(i);
+                }
+            }
+        }
+        let vct = self.panes[0].vct();
+        for i in added {
+            self.panes[i].link_x(vct);
+        }
+
+        // math expressions may reference the traces of any pane
+        let all: Vec<Trace> = self
+            .groups
+            .iter()
+            .flat_map(|(_, traces)| traces.iter().cloned())
+            .collect();
+        for pane in self.panes.iter_mut() {
+            pane.update(viewport::CompositeMsg {
+                content_msg: PlotMsg::MathSources(all.clone()),
+                viewport_msg: viewport::Msg::None,
+            });
+        }
+        let content_msgs: Vec<PlotMsg> = (0..self.panes.len())
+            .map(|i| {
+                let (traces, legend): (Vec<Vec<Trace>>, Vec<(String, usize)>) = self
+                    .groups
+                    .iter()
+                    .filter(|(name, _)| self.pane_of(name) == i)
+                    .map(|(name, traces)| (traces.clone(), (name.clone(), traces.len())))
+                    .unzip();
+                PlotMsg::Traces(traces.concat(), legend)
+            })
+            .collect();
+        for (pane, content_msg) in self.panes.iter_mut().zip(content_msgs) {
+            pane.update(viewport::CompositeMsg {
+                content_msg,
+                viewport_msg: viewport::Msg::None,
+            });
+        }
+    }
+    /// sets every pane to the widest x range of the panes
+    fn link_widest_x(&mut self) {
+        if let Some(vct) = self
+            .panes
+            .iter()
+            .map(|p| p.vct())
+            .min_by(|a, b| a.x_scale().total_cmp(&b.x_scale()))
+        {
+            for pane in self.panes.iter_mut() {
+                pane.link_x(vct);
+            }
+        }
+    }
+    /// sets the waveform math expressions of the active pane
+    fn set_math(&mut self, exprs: Vec<String>) {
+        self.pane_mut().update(viewport::CompositeMsg {
+            content_msg: PlotMsg::Math(exprs),
+            viewport_msg: viewport::Msg::None,
+        });
+        self.measured = None;
+    }
+}
@@ -0,0 +1,314 @@
+//! spectrum
+//! fft of transient traces, with harmonic distortion and signal to noise ratio of the fundamental
+
+use std::f64::consts::PI;
+use std::fmt::Display;
+
+use num::Complex;
+
+use crate::analysis::trace::{Trace, TraceSource};
+use crate::schematic::eng;
+use crate::transforms::VSPoint;
+
+/// most samples taken of a trace
+const MAX_SAMPLES: usize = 1 << 16;
+/// highest harmonic included in the total harmonic distortion
+const HARMONICS: usize = 10;
+
+/// window applied to the samples before the fft
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Window {
+    /// no window, for signals periodic in the time range
+    Rectangular,
+    /// hann window
+    #[default]
+    Hann,
+    /// blackman window, lowest leakage
+    Blackman,
+}
+
+impl Window {
+    /// all variants, for use in pick lists
+    pub const ALL: [Window; 3] = [Window::Rectangular, Window::Hann, Window::Blackman];
+    /// returns the weight of sample i of n
+    fn weight(&self, i: usize, n: usize) -> f64 {
+        let a = 2.0 * PI * i as f64 / n as f64;
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => 0.5 - 0.5 * a.cos(),
+            Window::Blackman => 0.42 - 0.5 * a.cos() + 0.08 * (2.0 * a).cos(),
+        }
+    }
+    /// returns the number of bins either side of a tone which hold its power
+    fn lobe(&self) -> usize {
+        match self {
+            Window::Rectangular => 1,
+            Window::Hann => 2,
+            Window::Blackman => 3,
+        }
+    }
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Window::Rectangular => "rectangular",
+                Window::Hann => "hann",
+                Window::Blackman => "blackman",
+            }
+        )
+    }
+}
+
+/// single sided spectrum of a trace
+#[derive(Debug, Clone)]
+pub struct Spectrum {
+    /// frequency of each bin
+    pub freq: Vec<f64>,
+    /// amplitude and phase of each bin
+    pub bins: Vec<Complex<f64>>,
+    /// number of uniform samples taken
+    pub samples: usize,
+    /// index of the bin of the fundamental, the largest bin outside the lobe of dc
+    pub fundamental: usize,
+    /// total harmonic distortion, as a ratio of rms values
+    pub thd: Option<f64>,
+    /// signal to noise ratio in dB, excluding dc and harmonics
+    pub snr: Option<f64>,
+}
+
+impl Spectrum {
+    /// returns the spectrum of points, which are in order of x. Points are resampled to uniform steps and windowed.
+    /// None if there are too few points
+    pub fn new(points: &[VSPoint], window: Window) -> Option<Spectrum> {
+        let (first, last) = (points.first()?, points.last()?);
+        let span = (last.x - first.x) as f64;
+        if points.len() < 4 || span <= 0.0 {
+            return None;
+        }
+        let n = points.len().next_power_of_two().min(MAX_SAMPLES);
+        let dt = span / n as f64;
+        let samples = resample(points, first.x as f64, dt, n);
+        let gain: f64 = (0..n).map(|i| window.weight(i, n)).sum();
+        let mut buf: Vec<Complex<f64>> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Complex::new(y * window.weight(i, n), 0.0))
+            .collect();
+        fft(&mut buf);
+
+        let m = n / 2;
+        let bins: Vec<Complex<f64>> = buf[..=m]
+            .iter()
+            .enumerate()
+            .map(|(k, x)| {
+                // energy of the negative frequencies is folded onto the positive ones
+                let fold = if k == 0 || k == m { 1.0 } else { 2.0 };
+                *x * (fold / gain)
+            })
+            .collect();
+        let freq = (0..=m).map(|k| k as f64 / span).collect();
+        // power held in the lobe around each tone
+        let lobe = window.lobe();
+        // a dc offset leaks into the bins of its lobe
+        let fundamental = ((lobe + 1)..=m)
+            .max_by(|a, b| bins[*a].norm().total_cmp(&bins[*b].norm()))
+            .unwrap_or(m);
+        let power = |c: usize| -> f64 {
+            (c.saturating_sub(lobe)..=(c + lobe).min(m))
+                .map(|k| bins[k].norm_sqr())
+                .sum()
+        };
+        let harmonics: Vec<usize> = (2..=HARMONICS)
+            .map(|h| h * fundamental)
+            .filter(|k| *k <= m)
+            .collect();
+        let p_fund = power(fundamental);
+        let p_harm: f64 = harmonics.iter().map(|k| power(*k)).sum();
+        let tone = |k: usize| {
+            k <= lobe
+                || k.abs_diff(fundamental) <= lobe
+                || harmonics.iter().any(|h| k.abs_diff(*h) <= lobe)
+        };
+        let p_noise: f64 = (1..=m)
+            .filter(|k| !tone(*k))
+            .map(|k| bins[k].norm_sqr())
+            .sum();
+        Some(Spectrum {
+            freq,
+            bins,
+            samples: n,
+            fundamental,
+            thd: (p_fund > 0.0).then(|| (p_harm / p_fund).sqrt()),
+            snr: (p_fund > 0.0 && p_noise > 0.0).then(|| 10.0 * (p_fund / p_noise).log10()),
+        })
+    }
+    /// returns magnitude (dB) and phase (degrees) traces of the spectrum of the trace named name
+    pub fn traces(&self, name: &str) -> Vec<Trace> {
+        let trace = |suffix: &str, unit: &str, f: fn(&Complex<f64>) -> f64| {
+            Trace::new(
+                format!("{} {}", name, suffix),
+                unit,
+                "Hz",
+                TraceSource::Fft,
+                self.freq
+                    .iter()
+                    .zip(self.bins.iter())
+                    .map(|(f_k, b)| VSPoint::new(*f_k as f32, f(b) as f32))
+                    .collect(),
+            )
+        };
+        vec![
+            trace("mag", "dB", |b| 20.0 * b.norm().log10()),
+            trace("phase", "deg", |b| b.arg().to_degrees()),
+        ]
+    }
+    /// returns the readout of the fundamental, with the amplitude in unit, thd and snr
+    pub fn readout(&self, window: Window, unit: &str) -> Vec<String> {
+        let mut lines = vec![
+            format!("{} window, {} samples", window, self.samples),
+            format!(
+                "fundamental: {}Hz, {}{}",
+                eng::format(self.freq[self.fundamental]),
+                eng::format(self.bins[self.fundamental].norm()),
+                unit
+            ),
+        ];
+        lines.push(match self.thd {
+            Some(thd) => format!("THD: {:.3}% ({:.1}dB)", thd * 100.0, 20.0 * thd.log10()),
+            None => String::from("THD: -"),
+        });
+        lines.push(match self.snr {
+            Some(snr) => format!("SNR: {:.1}dB", snr),
+            None => String::from("SNR: -"),
+        });
+        lines
+    }
+}
+
+/// returns n values of points at uniform steps dt from x0, interpolated linearly
+fn resample(points: &[VSPoint], x0: f64, dt: f64, n: usize) -> Vec<f64> {
+    let mut j = 0;
+    (0..n)
+        .map(|i| {
+            let x = x0 + i as f64 * dt;
+            while j + 2 < points.len() && (points[j + 1].x as f64) < x {
+                j += 1;
+            }
+            let (a, b) = (points[j], points[j + 1]);
+            let (ax, bx) = (a.x as f64, b.x as f64);
+            if bx == ax {
+                a.y as f64
+            } else {
+                a.y as f64 + (b.y - a.y) as f64 * ((x - ax) / (bx - ax)).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+/// in place radix 2 fft, the length of buf must be a power of two
+fn fft(buf: &mut [Complex<f64>]) {
+    let n = buf.len();
+    // bit reversed order
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let w = Complex::from_polar(1.0, -2.0 * PI / len as f64);
+        for start in (0..n).step_by(len) {
+            let mut wk = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let a = buf[start + k];
+                let b = buf[start + k + len / 2] * wk;
+                buf[start + k] = a + b;
+                buf[start + k + len / 2] = a - b;
+                wk *= w;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns 1024 points over one second of dc plus a sine of amplitude a at 8Hz
+    /// and a third harmonic of amplitude a3, a whole number of cycles over the samples
+    fn tone(dc: f64, a: f64, a3: f64) -> Vec<VSPoint> {
+        (0..1024)
+            .map(|i| {
+                // the fft resamples the second to 1024 steps, holding exactly 8 cycles
+                let t = i as f64 / 1023.0;
+                let phase = 2.0 * PI * 8.0 * t;
+                VSPoint::new(
+                    t as f32,
+                    (dc + a * phase.sin() + a3 * (3.0 * phase).sin()) as f32,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pure_sine_thd() {
+        let spectrum = Spectrum::new(&tone(0.0, 1.0, 0.0), Window::Rectangular).unwrap();
+        assert_eq!(spectrum.fundamental, 8);
+        assert!((spectrum.bins[8].norm() - 1.0).abs() < 1e-2);
+        assert!(spectrum.thd.unwrap() < 1e-3);
+    }
+
+    #[test]
+    fn third_harmonic_thd() {
+        let spectrum = Spectrum::new(&tone(0.0, 1.0, 0.1), Window::Rectangular).unwrap();
+        assert_eq!(spectrum.samples, 1024);
+        assert_eq!(spectrum.fundamental, 8);
+        assert!((spectrum.bins[8].norm() - 1.0).abs() < 1e-2);
+        assert!((spectrum.thd.unwrap() - 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sine_with_dc_offset() {
+        for window in Window::ALL {
+            let spectrum = Spectrum::new(&tone(2.5, 0.01, 0.0), window).unwrap();
+            assert_eq!(spectrum.fundamental, 8, "{} window", window);
+            assert!((spectrum.bins[0].norm() - 2.5).abs() < 1e-3);
+            assert!((spectrum.bins[8].norm() - 0.01).abs() < 1e-4);
+            assert!(spectrum.thd.unwrap() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn every_window() {
+        for window in Window::ALL {
+            let spectrum = Spectrum::new(&tone(0.0, 1.0, 0.1), window).unwrap();
+            assert_eq!(spectrum.fundamental, 8, "{} window", window);
+            assert!((spectrum.freq[8] - 8.0).abs() < 1e-2);
+            assert!((spectrum.bins[8].norm() - 1.0).abs() < 1e-2);
+            assert!(
+                (spectrum.thd.unwrap() - 0.1).abs() < 1e-3,
+                "{} window",
+                window
+            );
+            assert!(spectrum.snr.unwrap() > 40.0, "{} window", window);
+        }
+    }
+
+    #[test]
+    fn too_few_points() {
+        let points = [VSPoint::new(0.0, 0.0), VSPoint::new(1.0, 1.0)];
+        assert!(Spectrum::new(&points, Window::Hann).is_none());
+    }
+}
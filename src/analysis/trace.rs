@@ -0,0 +1,136 @@
+//! trace
+//! named simulation results, as passed from the schematic to the plot
+
+use std::fmt::Display;
+
+use crate::transforms::VSPoint;
+use iced::Color;
+
+/// analysis a trace results from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceSource {
+    /// transient analysis
+    Tran,
+    /// ac small-signal analysis
+    Ac,
+    /// dc transfer curve analysis
+    Dc,
+    /// noise analysis
+    Noise,
+    /// pole-zero analysis
+    Pz,
+    /// fft of a transient trace
+    Fft,
+    /// measurements of a monte carlo or corner analysis
+    Mc,
+}
+
+impl Display for TraceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TraceSource::Tran => "tran",
+                TraceSource::Ac => "ac",
+                TraceSource::Dc => "dc",
+                TraceSource::Noise => "noise",
+                TraceSource::Pz => "pz",
+                TraceSource::Fft => "fft",
+                TraceSource::Mc => "mc",
+            }
+        )
+    }
+}
+
+/// a named trace of simulation results
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// name of the trace, e.g. the ngspice vector name
+    pub name: String,
+    /// unit of the y values, e.g. `V`
+    pub unit: String,
+    /// unit of the x values, e.g. `s`
+    pub x_unit: String,
+    /// analysis the trace results from
+    pub source: TraceSource,
+    /// color of the trace. If None, the plot colors the trace by its legend entry
+    pub color: Option<Color>,
+    /// the points of the trace, in order
+    pub points: Vec<VSPoint>,
+}
+
+impl Trace {
+    /// returns a new trace without a set color
+    pub fn new(
+        name: impl Into<String>,
+        unit: impl Into<String>,
+        x_unit: impl Into<String>,
+        source: TraceSource,
+        points: Vec<VSPoint>,
+    ) -> Self {
+        Trace {
+            name: name.into(),
+            unit: unit.into(),
+            x_unit: x_unit.into(),
+            source,
+            color: None,
+            points,
+        }
+    }
+    /// returns the name of the trace with its unit and source, e.g. `v(out) [V] (tran)`
+    pub fn label(&self) -> String {
+        if self.unit.is_empty() {
+            format!("{} ({})", self.name, self.source)
+        } else {
+            format!("{} [{}] ({})", self.name, self.unit, self.source)
+        }
+    }
+    /// returns the value at x, interpolated linearly between the samples either side of x
+    pub fn value_at(&self, x: f32) -> Option<f32> {
+        self.points.windows(2).find_map(|w| {
+            let (a, b) = (w[0], w[1]);
+            if (a.x <= x && x <= b.x) || (b.x <= x && x <= a.x) {
+                if a.x == b.x {
+                    Some(a.y)
+                } else {
+                    Some(a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x))
+                }
+            } else {
+                None
+            }
+        })
+    }
+    /// returns the points with x from `from` to `to`, with the values at either end interpolated
+    pub fn window(&self, from: f32, to: f32) -> Vec<VSPoint> {
+        let mut points: Vec<VSPoint> = self
+            .value_at(from)
+            .map(|y| VSPoint::new(from, y))
+            .into_iter()
+            .collect();
+        points.extend(self.points.iter().filter(|p| from < p.x && p.x < to));
+        points.extend(self.value_at(to).map(|y| VSPoint::new(to, y)));
+        points
+    }
+}
+
+/// returns the unit of the ngspice vector or signal name, `A` for currents and `V` otherwise
+pub fn unit_of(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    if name.contains("#branch") || name.starts_with('@') || name.starts_with("i(") {
+        "A"
+    } else {
+        "V"
+    }
+}
+
+/// returns the unit of the ngspice dc sweep scale vector, e.g. `V` for `v-sweep`
+pub fn sweep_unit(scale: &str) -> &'static str {
+    match scale.split('-').next() {
+        Some("v") => "V",
+        Some("i") => "A",
+        Some("temp") => "degC",
+        Some("res") => "Ohm",
+        _ => "",
+    }
+}
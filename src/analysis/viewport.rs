@@ -18,6 +18,7 @@ use iced::{
     },
     Color, Length, Rectangle, Size, Theme,
 };
+use std::cell::Cell;
 
 /// viewport to canvas space transform with independent x-y aspect ratios
 #[derive(Debug, Clone, Copy)]
@@ -51,6 +52,17 @@ impl VCTransformFreeAspect {
     pub fn then_scale(&self, x_scale: f32, y_scale: f32) -> Self {
         Self(self.0.then_scale(x_scale, y_scale))
     }
+    /// returns the transform with the x scale and translation of other, keeping its own along y
+    pub fn with_x_of(&self, other: &Self) -> Self {
+        Self(VCTransform::new(
+            other.0.m11,
+            0.0,
+            0.0,
+            self.0.m22,
+            other.0.m31,
+            self.0.m32,
+        ))
+    }
     /// transform a point
     pub fn transform_point(&self, vsp: VSPoint) -> CSPoint {
         self.0.transform_point(vsp)
@@ -154,6 +166,8 @@ where
 
     /// viewport to canvas transform
     vct: VCTransformFreeAspect,
+    /// canvas bounds as of the last canvas event, empty until the canvas receives an event
+    canvas_bounds: Cell<CSBox>,
 
     /// the cursor positions in the different spaces
     curpos: (CSPoint, VSPoint, SSPoint),
@@ -186,6 +200,10 @@ where
             CSPoint::new(bounds.x, bounds.y),
             CSPoint::new(bounds.width, bounds.height),
         ]);
+        self.canvas_bounds.set(CSBox::from_points([
+            CSPoint::origin(),
+            CSPoint::new(bounds.width, bounds.height),
+        ]));
 
         self.active_cache.clear();
 
@@ -305,6 +323,7 @@ where
             min_zoom,
             max_zoom,
             vct,
+            canvas_bounds: Default::default(),
             content: C::default(),
             active_cache: Default::default(),
             passive_cache: Default::default(),
@@ -475,6 +494,43 @@ where
         Msg::NewView(vct, csp)
     }
 
+    /// zoom out such that the content stays in view, if it has grown past the visible area
+    pub fn extend_to_content(&mut self) {
+        let csb = self.canvas_bounds.get();
+        let vsb = self.content.bounds();
+        if csb.is_empty() || vsb.is_empty() {
+            return;
+        }
+        let visible = self.cv_transform().outer_transformed_box(&csb);
+        if visible.contains_box(&vsb) {
+            return;
+        }
+        self.vct = VCTransformFreeAspect::fit_bounds(
+            csb,
+            visible.union(&vsb),
+            self.min_zoom,
+            self.max_zoom,
+        );
+        self.passive_cache.clear();
+    }
+
+    /// fit the view to the content, e.g. after the content was rescaled
+    pub fn fit_to_content(&mut self) {
+        let csb = self.canvas_bounds.get();
+        let vsb = self.content.bounds();
+        if csb.is_empty() || vsb.is_empty() {
+            return;
+        }
+        self.vct = VCTransformFreeAspect::fit_bounds(csb, vsb, self.min_zoom, self.max_zoom);
+        self.passive_cache.clear();
+    }
+
+    /// takes the x scale and translation of vct, so that viewports stacked above each other show the same x range
+    pub fn link_x(&mut self, vct: VCTransformFreeAspect) {
+        self.vct = self.vct.with_x_of(&vct);
+        self.passive_cache.clear();
+    }
+
     /// pan by vector v
     pub fn pan(&self, csp_now: CSPoint, csp_prev: CSPoint) -> Msg {
         let v = self.cv_transform().transform_vector(csp_now - csp_prev);
@@ -0,0 +1,523 @@
+//! waveform math
+//! derived traces from expressions over the plotted traces, e.g. `v(out)-v(in)` or `db(v(out))`
+
+use num::Complex;
+
+use crate::analysis::trace::{self, Trace, TraceSource};
+use crate::schematic::lexer::Lexer;
+use crate::transforms::VSPoint;
+
+/// a signal sampled over x, e.g. a node voltage over time. Values are complex to carry ac results
+#[derive(Debug, Clone)]
+struct Wave {
+    /// x values, in ascending order
+    x: Vec<f64>,
+    /// value at each x
+    y: Vec<Complex<f64>>,
+    /// unit of the values
+    unit: String,
+    /// unit of x
+    x_unit: String,
+    /// analysis the wave results from
+    source: TraceSource,
+}
+
+impl Wave {
+    /// returns the value at x, interpolated linearly between samples and held beyond either end
+    fn sample(&self, x: f64) -> Complex<f64> {
+        let i = self.x.partition_point(|xi| *xi < x);
+        match (i, self.x.len()) {
+            (_, 0) => Complex::new(0.0, 0.0),
+            (0, _) => self.y[0],
+            (i, n) if i >= n => self.y[n - 1],
+            (i, _) => {
+                let (x0, x1) = (self.x[i - 1], self.x[i]);
+                if x1 == x0 {
+                    self.y[i]
+                } else {
+                    self.y[i - 1] + (self.y[i] - self.y[i - 1]) * ((x - x0) / (x1 - x0))
+                }
+            }
+        }
+    }
+    /// returns the wave with f applied to every value, in unit
+    fn map(self, unit: String, f: impl Fn(Complex<f64>) -> Complex<f64>) -> Wave {
+        Wave {
+            y: self.y.into_iter().map(f).collect(),
+            unit,
+            ..self
+        }
+    }
+    /// returns the derivative over x, by central differences
+    fn derivative(self) -> Wave {
+        let n = self.x.len();
+        let y = (0..n)
+            .map(|i| {
+                let (a, b) = (i.saturating_sub(1), (i + 1).min(n - 1));
+                let dx = self.x[b] - self.x[a];
+                if dx == 0.0 {
+                    Complex::new(0.0, 0.0)
+                } else {
+                    (self.y[b] - self.y[a]) / dx
+                }
+            })
+            .collect();
+        let unit = format!("{}/{}", self.unit, self.x_unit);
+        Wave { y, unit, ..self }
+    }
+    /// returns the running integral over x, by the trapezoidal rule
+    fn integral(self) -> Wave {
+        let mut sum = Complex::new(0.0, 0.0);
+        let y = (0..self.x.len())
+            .map(|i| {
+                if i > 0 {
+                    sum += (self.y[i] + self.y[i - 1]) * ((self.x[i] - self.x[i - 1]) / 2.0);
+                }
+                sum
+            })
+            .collect();
+        let unit = format!("{}*{}", self.unit, self.x_unit);
+        Wave { y, unit, ..self }
+    }
+    /// returns the mean of the samples within width before each x
+    fn moving_average(self, width: f64) -> Wave {
+        let mut sum = Complex::new(0.0, 0.0);
+        let mut start = 0;
+        let y = (0..self.x.len())
+            .map(|i| {
+                sum += self.y[i];
+                while self.x[i] - self.x[start] > width {
+                    sum -= self.y[start];
+                    start += 1;
+                }
+                sum / (i + 1 - start) as f64
+            })
+            .collect();
+        Wave { y, ..self }
+    }
+}
+
+/// the value of an expression, either a constant or a wave
+#[derive(Debug, Clone)]
+enum Value {
+    Scalar(Complex<f64>),
+    Wave(Wave),
+}
+
+/// evaluates expr over traces and returns the result as a trace named expr.
+/// Signals are referenced as `v(net)`, `v(net1, net2)`, `i(device)` or by trace name, the first trace of a name is used.
+/// Waves over different x values are resampled to the x values of the left operand
+pub fn eval(expr: &str, traces: &[Trace]) -> Result<Trace, String> {
+    let mut parser = Parser {
+        lex: Lexer::new(expr),
+        traces,
+    };
+    let v = parser.expr()?;
+    parser.lex.skip_ws();
+    if !parser.lex.rest().is_empty() {
+        return Err(format!("unexpected '{}' in {}", parser.lex.rest(), expr));
+    }
+    let Value::Wave(w) = v else {
+        return Err(String::from("expression does not reference a trace"));
+    };
+    // complex results, e.g. the ratio of two ac voltages, are plotted as magnitude
+    let complex =
+        w.y.iter()
+            .any(|y| y.im.abs() > 1e-12 * y.re.abs().max(1e-30));
+    let points =
+        w.x.iter()
+            .zip(w.y.iter())
+            .map(|(x, y)| VSPoint::new(*x as f32, (if complex { y.norm() } else { y.re }) as f32))
+            .collect();
+    Ok(Trace::new(expr.trim(), w.unit, w.x_unit, w.source, points))
+}
+
+/// applies op to a and b, resampling b to the x values of a if both are waves
+fn binary(
+    a: Value,
+    b: Value,
+    unit: impl Fn(&str, &str) -> String,
+    op: impl Fn(Complex<f64>, Complex<f64>) -> Complex<f64>,
+) -> Result<Value, String> {
+    Ok(match (a, b) {
+        (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(op(a, b)),
+        (Value::Wave(a), Value::Scalar(b)) => {
+            let unit = unit(&a.unit, "");
+            Value::Wave(a.map(unit, |y| op(y, b)))
+        }
+        (Value::Scalar(a), Value::Wave(b)) => {
+            let unit = unit("", &b.unit);
+            Value::Wave(b.map(unit, |y| op(a, y)))
+        }
+        (Value::Wave(a), Value::Wave(b)) => {
+            if a.x_unit != b.x_unit {
+                return Err(format!(
+                    "cannot combine traces over {} and {}",
+                    a.x_unit, b.x_unit
+                ));
+            }
+            let y = if a.x == b.x {
+                a.y.iter()
+                    .zip(b.y.iter())
+                    .map(|(ya, yb)| op(*ya, *yb))
+                    .collect()
+            } else {
+                a.x.iter()
+                    .zip(a.y.iter())
+                    .map(|(x, ya)| op(*ya, b.sample(*x)))
+                    .collect()
+            };
+            let unit = unit(&a.unit, &b.unit);
+            Value::Wave(Wave { y, unit, ..a })
+        }
+    })
+}
+
+/// returns the unit of a sum of a and b
+fn sum_unit(a: &str, b: &str) -> String {
+    (if a.is_empty() { b } else { a }).to_string()
+}
+
+/// returns the unit of a product of a and b
+fn product_unit(a: &str, b: &str) -> String {
+    match (a, b) {
+        ("V", "A") | ("A", "V") => String::from("W"),
+        ("", u) | (u, "") => u.to_string(),
+        (a, b) => format!("{}*{}", a, b),
+    }
+}
+
+/// returns the unit of a quotient of a and b
+fn quotient_unit(a: &str, b: &str) -> String {
+    match (a, b) {
+        ("V", "A") => String::from("Ohm"),
+        (a, b) if a == b => String::new(),
+        (u, "") => u.to_string(),
+        ("", u) => format!("1/{}", u),
+        (a, b) => format!("{}/{}", a, b),
+    }
+}
+
+/// recursive descent parser of waveform expressions over complex values and waves
+///
+/// expr := term (('+' | '-') term)*
+/// term := unary (('*' | '/') unary)*
+/// unary := ('+' | '-') unary | power
+/// power := primary (('^' | '**') unary)?
+/// primary := number | name | name '(' expr (',' expr)* ')' | 'd/dt(' expr ')' | '(' expr ')'
+struct Parser<'a> {
+    lex: Lexer<'a>,
+    traces: &'a [Trace],
+}
+
+impl Parser<'_> {
+    fn expr(&mut self) -> Result<Value, String> {
+        let mut v = self.term()?;
+        loop {
+            if self.lex.eat("+") {
+                v = binary(v, self.term()?, sum_unit, |a, b| a + b)?;
+            } else if self.lex.eat("-") {
+                v = binary(v, self.term()?, sum_unit, |a, b| a - b)?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+    fn term(&mut self) -> Result<Value, String> {
+        let mut v = self.unary()?;
+        loop {
+            if self.lex.eat("*") {
+                v = binary(v, self.unary()?, product_unit, |a, b| a * b)?;
+            } else if self.lex.eat("/") {
+                v = binary(v, self.unary()?, quotient_unit, |a, b| a / b)?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+    fn unary(&mut self) -> Result<Value, String> {
+        if self.lex.eat("-") {
+            binary(
+                Value::Scalar(Complex::new(0.0, 0.0)),
+                self.unary()?,
+                sum_unit,
+                |a, b| a - b,
+            )
+        } else if self.lex.eat("+") {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+    fn power(&mut self) -> Result<Value, String> {
+        let base = self.primary()?;
+        if self.lex.eat("^") || self.lex.eat("**") {
+            binary(base, self.unary()?, |a, _| a.to_string(), |a, b| a.powc(b))
+        } else {
+            Ok(base)
+        }
+    }
+    fn primary(&mut self) -> Result<Value, String> {
+        self.lex.skip_ws();
+        if self.lex.eat("(") {
+            let v = self.expr()?;
+            return if self.lex.eat(")") {
+                Ok(v)
+            } else {
+                Err(String::from("expected )"))
+            };
+        }
+        if self.lex.eat("d/dt(") {
+            let v = self.expr()?;
+            if !self.lex.eat(")") {
+                return Err(String::from("expected )"));
+            }
+            return call("ddt", vec![v]);
+        }
+        match self.lex.peek() {
+            Some(c) if c.is_ascii_digit() || c == b'.' => self
+                .lex
+                .number()
+                .map(|v| Value::Scalar(Complex::new(v, 0.0))),
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                let name = self
+                    .lex
+                    .take_while(|c| c.is_ascii_alphanumeric() || c == b'_')
+                    .to_lowercase();
+                if !self.lex.eat("(") {
+                    return if name == "pi" {
+                        Ok(Value::Scalar(Complex::new(std::f64::consts::PI, 0.0)))
+                    } else {
+                        self.signal(&[name.as_str()]).map(Value::Wave)
+                    };
+                }
+                // v() and i() take node and device names rather than expressions
+                if name == "v" || name == "i" {
+                    let args: Vec<String> = self
+                        .lex
+                        .take_while(|c| c != b')')
+                        .split(',')
+                        .map(|a| a.trim().to_lowercase())
+                        .collect();
+                    if !self.lex.eat(")") {
+                        return Err(String::from("expected )"));
+                    }
+                    return match (name.as_str(), &args[..]) {
+                        ("v", [net]) => self.voltage(net).map(Value::Wave),
+                        ("v", [a, b]) => binary(
+                            Value::Wave(self.voltage(a)?),
+                            Value::Wave(self.voltage(b)?),
+                            sum_unit,
+                            |a, b| a - b,
+                        ),
+                        ("i", [device]) => self.current(device).map(Value::Wave),
+                        _ => Err(format!("{}() takes a node or device name", name)),
+                    };
+                }
+                let mut args = vec![self.expr()?];
+                while self.lex.eat(",") {
+                    args.push(self.expr()?);
+                }
+                if !self.lex.eat(")") {
+                    return Err(String::from("expected )"));
+                }
+                call(&name, args)
+            }
+            Some(c) => Err(format!("unexpected '{}'", c as char)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+    /// returns the voltage of net
+    fn voltage(&self, net: &str) -> Result<Wave, String> {
+        self.signal(&[net, &format!("v({})", net)])
+    }
+    /// returns the current through device, flowing in at its first terminal
+    fn current(&self, device: &str) -> Result<Wave, String> {
+        self.signal(&[
+            &format!("i({})", device),
+            &format!("@{}[i]", device),
+            &format!("{}#branch", device),
+            // current probes are named after the device terminal
+            &format!("i({}.p)", device),
+        ])
+        .or_else(|e| {
+            // a probe on the second terminal measures the current flowing out at the first
+            self.signal(&[&format!("i({}.n)", device)])
+                .map(|w| {
+                    let unit = w.unit.clone();
+                    w.map(unit, |y| -y)
+                })
+                .map_err(|_| e)
+        })
+    }
+    /// returns the first trace named one of names (case insensitive). Ac results are combined from their magnitude and phase traces
+    fn signal(&self, names: &[&str]) -> Result<Wave, String> {
+        let find = |name: &str| {
+            self.traces
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+        };
+        for name in names {
+            if let Some(t) = find(name) {
+                return Ok(Wave {
+                    x: t.points.iter().map(|p| p.x as f64).collect(),
+                    y: t.points
+                        .iter()
+                        .map(|p| Complex::new(p.y as f64, 0.0))
+                        .collect(),
+                    unit: t.unit.clone(),
+                    x_unit: t.x_unit.clone(),
+                    source: t.source,
+                });
+            }
+            if let (Some(mag), Some(phase)) = (
+                find(&format!("{} mag", name)),
+                find(&format!("{} phase", name)),
+            ) {
+                return Ok(Wave {
+                    x: mag.points.iter().map(|p| p.x as f64).collect(),
+                    y: mag
+                        .points
+                        .iter()
+                        .zip(phase.points.iter())
+                        .map(|(m, p)| {
+                            Complex::from_polar(
+                                10f64.powf(m.y as f64 / 20.0),
+                                (p.y as f64).to_radians(),
+                            )
+                        })
+                        .collect(),
+                    unit: trace::unit_of(name).to_string(),
+                    x_unit: mag.x_unit.clone(),
+                    source: mag.source,
+                });
+            }
+        }
+        Err(format!("no trace named {}", names[0]))
+    }
+}
+
+/// evaluates function name with args
+fn call(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    let unary = |unit: Option<&str>, f: fn(Complex<f64>) -> Complex<f64>| match &args[..] {
+        [Value::Scalar(v)] => Ok(Value::Scalar(f(*v))),
+        [Value::Wave(w)] => {
+            let unit = unit.map_or(w.unit.clone(), |u| u.to_string());
+            Ok(Value::Wave(w.clone().map(unit, f)))
+        }
+        _ => Err(format!("{} takes 1 argument", name)),
+    };
+    let wave = || match &args[..] {
+        [Value::Wave(w)] => Ok(w.clone()),
+        _ => Err(format!("{} takes 1 trace", name)),
+    };
+    match name {
+        "db" => unary(Some("dB"), |v| Complex::new(20.0 * v.norm().log10(), 0.0)),
+        "ph" | "phase" => unary(Some("deg"), |v| Complex::new(v.arg().to_degrees(), 0.0)),
+        "abs" | "mag" => unary(None, |v| Complex::new(v.norm(), 0.0)),
+        "re" | "real" => unary(None, |v| Complex::new(v.re, 0.0)),
+        "im" | "imag" => unary(None, |v| Complex::new(v.im, 0.0)),
+        "sqrt" => unary(None, |v| v.sqrt()),
+        "exp" => unary(Some(""), |v| v.exp()),
+        "ln" | "log" => unary(Some(""), |v| v.ln()),
+        "log10" => unary(Some(""), |v| v.log(10.0)),
+        "ddt" | "deriv" => wave().map(|w| Value::Wave(w.derivative())),
+        "integ" | "integral" => wave().map(|w| Value::Wave(w.integral())),
+        "avg" | "movavg" => match &args[..] {
+            [Value::Wave(w), Value::Scalar(width)] if width.re > 0.0 => {
+                Ok(Value::Wave(w.clone().moving_average(width.re)))
+            }
+            _ => Err(format!("{} takes a trace and a window width", name)),
+        },
+        _ => Err(format!("unknown function: {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns a transient trace named name with values f(x) at x = 0, 1, ..., n - 1
+    fn trace(name: &str, n: usize, f: impl Fn(f32) -> f32) -> Trace {
+        Trace::new(
+            name,
+            trace::unit_of(name),
+            "s",
+            TraceSource::Tran,
+            (0..n)
+                .map(|i| VSPoint::new(i as f32, f(i as f32)))
+                .collect(),
+        )
+    }
+
+    /// returns the y values of the trace of expr over traces
+    fn values(expr: &str, traces: &[Trace]) -> Vec<f32> {
+        eval(expr, traces)
+            .unwrap()
+            .points
+            .iter()
+            .map(|p| p.y)
+            .collect()
+    }
+
+    #[test]
+    fn precedence() {
+        let traces = [trace("v(a)", 3, |x| x)];
+        // multiplication before addition, power before negation
+        assert_eq!(values("v(a) + 2 * 3", &traces), [6.0, 7.0, 8.0]);
+        assert_eq!(values("(v(a) + 2) * 3", &traces), [6.0, 9.0, 12.0]);
+        assert_eq!(values("-v(a)^2", &traces), [0.0, -1.0, -4.0]);
+        assert_eq!(values("v(a) - 1 - 1", &traces), [-2.0, -1.0, 0.0]);
+        assert_eq!(values("8 / 2 / (v(a) + 1)", &traces), [4.0, 2.0, 4.0 / 3.0]);
+    }
+
+    #[test]
+    fn units() {
+        let traces = [
+            trace("v(a)", 2, |x| x),
+            trace("v(b)", 2, |_| 1.0),
+            trace("i(r1)", 2, |_| 1e-3),
+        ];
+        let unit = |expr: &str| eval(expr, &traces).unwrap().unit;
+        assert_eq!(unit("v(a) - v(b)"), "V");
+        assert_eq!(unit("v(a) * i(r1)"), "W");
+        assert_eq!(unit("v(a) / i(r1)"), "Ohm");
+        assert_eq!(unit("v(a) / v(b)"), "");
+        assert_eq!(unit("2 * v(a)"), "V");
+        assert_eq!(unit("db(v(a))"), "dB");
+        assert_eq!(unit("d/dt(v(a))"), "V/s");
+    }
+
+    #[test]
+    fn binary_resamples_to_left_operand() {
+        // b is sampled at every other x of a, and interpolated in between
+        let a = trace("v(a)", 5, |_| 0.0);
+        let b = Trace::new(
+            "v(b)",
+            "V",
+            "s",
+            TraceSource::Tran,
+            vec![
+                VSPoint::new(0.0, 0.0),
+                VSPoint::new(2.0, 2.0),
+                VSPoint::new(4.0, 0.0),
+            ],
+        );
+        let traces = [a, b];
+        assert_eq!(values("v(a) + v(b)", &traces), [0.0, 1.0, 2.0, 1.0, 0.0]);
+        // the result takes the x values of the left operand
+        assert_eq!(eval("v(b) + v(a)", &traces).unwrap().points.len(), 3);
+    }
+
+    #[test]
+    fn errors() {
+        let traces = [trace("v(a)", 2, |x| x)];
+        assert!(eval("v(missing)", &traces).is_err());
+        assert!(eval("v(a) +", &traces).is_err());
+        assert!(eval("(v(a)", &traces).is_err());
+        assert!(eval("2 * 3", &traces).is_err());
+        assert!(eval("foo(v(a))", &traces).is_err());
+        let ac = Trace::new("v(b)", "V", "Hz", TraceSource::Ac, vec![]);
+        assert!(eval("v(a) + v(b)", &[traces[0].clone(), ac]).is_err());
+    }
+}
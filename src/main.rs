@@ -9,10 +9,10 @@ mod transforms;
 
 use analysis::plot_page::{PlotPage, PlotPageMsg};
 use enum_dispatch::enum_dispatch;
-use schematic::circuit::CircuitSchematicPage;
+use schematic::circuit::{CircuitPageMsg, CircuitSchematicPage};
 use schematic::symbols::SymbolDesignerPage;
 
-use iced::{executor, Application, Command, Element, Settings, Theme};
+use iced::{executor, Application, Command, Element, Settings, Subscription, Theme};
 
 use iced_aw::{TabLabel, Tabs};
 
@@ -37,6 +37,10 @@ pub struct Circe {
 
     /// unstable - early development - for viewing plots of simulation results
     plot_view: PlotPage,
+    /// spectra of transient traces
+    spectrum_view: PlotPage,
+    /// histogram of the measurement of monte carlo analyses
+    histogram_view: PlotPage,
     /// circuits schematic for schematic capture
     circuit_schematic: CircuitSchematicPage,
     /// dev use only - for drawing custom devices or new device graphics
@@ -48,6 +52,8 @@ pub enum Msg {
     DesignerMsg(schematic::symbols::DevicePageMsg),
     SchematicMsg(schematic::circuit::CircuitPageMsg),
     PlotViewMsg(analysis::plot_page::PlotPageMsg),
+    SpectrumViewMsg(analysis::plot_page::PlotPageMsg),
+    HistogramViewMsg(analysis::plot_page::PlotPageMsg),
     TabSel(usize),
 }
 
@@ -63,6 +69,8 @@ impl Application for Circe {
                 circuit_schematic: CircuitSchematicPage::default(),
                 symbol_designer: SymbolDesignerPage::default(),
                 plot_view: PlotPage::default(),
+                spectrum_view: PlotPage::default(),
+                histogram_view: PlotPage::default(),
                 active_tab: 1,
             },
             Command::none(),
@@ -77,34 +85,76 @@ impl Application for Circe {
         match message {
             Msg::TabSel(i) => {
                 self.active_tab = i;
-
-                // transfer simulation results from circuit_schematic to plot
-                if let Some(traces) = self.circuit_schematic.traces.take() {
-                    let msg = PlotPageMsg::Traces(traces);
-                    self.plot_view.update(msg);
-                }
             }
             Msg::DesignerMsg(device_designer_msg) => {
                 self.symbol_designer.update(device_designer_msg);
             }
             Msg::PlotViewMsg(plot_msg) => {
                 self.plot_view.update(plot_msg);
+                // show the spectrum of a transient trace in its own tab
+                if let Some((traces, notes)) = self.plot_view.spectrum.take() {
+                    self.spectrum_view
+                        .update(PlotPageMsg::Spectrum(traces, notes));
+                    self.active_tab = 3;
+                }
+            }
+            Msg::SpectrumViewMsg(plot_msg) => {
+                self.spectrum_view.update(plot_msg);
+                self.spectrum_view.spectrum = None;
+            }
+            Msg::HistogramViewMsg(plot_msg) => {
+                self.histogram_view.update(plot_msg);
+                self.histogram_view.spectrum = None;
             }
             Msg::SchematicMsg(schematic_msg) => {
                 self.circuit_schematic.update(schematic_msg);
+
+                // grow the plot while a live simulation runs, staying on the current tab
+                if let Some(traces) = self.circuit_schematic.live_traces.take() {
+                    let legend = self.circuit_schematic.legend.clone();
+                    self.plot_view
+                        .update(PlotPageMsg::LiveTraces(traces, legend));
+                }
+                // transfer simulation results from circuit_schematic to plot and show them
+                if let Some(traces) = self.circuit_schematic.traces.take() {
+                    let legend = std::mem::take(&mut self.circuit_schematic.legend);
+                    let msg = PlotPageMsg::Traces(traces, legend);
+                    self.plot_view.update(msg);
+                    self.active_tab = 0;
+                }
+                if let Some(traces) = self.circuit_schematic.histogram.take() {
+                    self.histogram_view
+                        .update(PlotPageMsg::Traces(traces, vec![]));
+                }
+                if let Some(s) = self.circuit_schematic.clipboard.take() {
+                    return iced::clipboard::write(s);
+                }
             }
         }
         Command::none()
     }
 
+    fn subscription(&self) -> Subscription<Msg> {
+        if self.circuit_schematic.is_streaming() {
+            iced::time::every(std::time::Duration::from_millis(100))
+                .map(|_| Msg::SchematicMsg(CircuitPageMsg::StreamTick))
+        } else {
+            Subscription::none()
+        }
+    }
+
     fn view(&self) -> Element<Msg> {
         let schematic = self.circuit_schematic.view().map(Msg::SchematicMsg);
         let plot = self.plot_view.view().map(Msg::PlotViewMsg);
+        let spectrum = self.spectrum_view.view().map(Msg::SpectrumViewMsg);
+        let histogram = self.histogram_view.view().map(Msg::HistogramViewMsg);
         let devices = self.symbol_designer.view().map(Msg::DesignerMsg);
 
         let tabs = Tabs::with_tabs(
             vec![
                 (0, TabLabel::Text("Graphs".to_string()), plot),
+                (3, TabLabel::Text("Spectrum".to_string()), spectrum),
+                (4, TabLabel::Text("Histogram".to_string()), histogram),
                 (1, TabLabel::Text("Schematic".to_string()), schematic),
                 (2, TabLabel::Text("Device Designer".to_string()), devices),
             ],
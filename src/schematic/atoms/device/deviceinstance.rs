@@ -3,19 +3,22 @@
 use std::hash::Hasher;
 
 use super::devicetype::DeviceClass;
+use super::params::Variation;
 
 use iced::{
-    widget::canvas::{Frame, Text},
+    widget::canvas::{stroke, Frame, Path, Stroke, Text},
     Color,
 };
 
 use crate::{
     schematic::{
+        eng,
         interactable::{Interactable, Interactive},
         layers::Nets,
     },
     transforms::{
-        sst_to_vvt, vvt_to_sst, Point, SSPoint, SSTransform, VCTransform, VSPoint, VVTransform,
+        sst_to_vvt, vvt_to_sst, Point, SSPoint, SSTransform, VCTransform, VSPoint, VSVec,
+        VVTransform,
     },
     Drawable,
 };
@@ -85,16 +88,62 @@ pub struct Device {
     /// the class of the device - is the device a resistor, ground, voltage source... ?
     class: DeviceClass,
 
+    /// relative tolerance of the device value, if any. E.g. 0.05 for 5%
+    tolerance: Option<f32>,
+    /// the device parameters with `{expressions}` evaluated, or the error of an invalid expression. None if there are no expressions
+    expr_value: Option<Result<String, String>>,
+
     /// vector of the connected net names in order of device ports
     connected_nets: Vec<String>,
-    /// vector of the connect net voltages in order of device ports
-    op: Vec<num::Complex<f32>>,
+    /// vector of the connect net voltages in order of device ports. None where ngspice sent no voltage for the net
+    op: Vec<Option<num::Complex<f32>>>,
+    /// operating point currents into the device ports, in order of device ports
+    op_currents: Vec<Option<f64>>,
 }
 impl Device {
     /// sets the device identifier watermark
     pub fn set_wm(&mut self, wm: usize) {
         self.id.wm = wm;
     }
+    /// returns the device identifier as it appears in the netlist
+    pub fn ng_id(&self) -> String {
+        self.id.ng_id()
+    }
+    /// returns the relative tolerance of the device value, if any
+    pub fn tolerance(&self) -> Option<f32> {
+        self.tolerance
+    }
+    /// sets the relative tolerance of the device value. Has no effect on the netlist if the device has no tolerable value
+    pub fn set_tolerance(&mut self, tolerance: Option<f32>) {
+        self.tolerance = tolerance;
+    }
+    /// sets the device parameters with `{expressions}` evaluated, for display on canvas
+    pub fn set_expr_value(&mut self, expr_value: Option<Result<String, String>>) {
+        self.expr_value = expr_value;
+    }
+    /// returns the device parameters for the netlist, with the device value varied according to variation if the device has a tolerance.
+    /// idx is the index of the next varied value in the netlist, and is incremented if the device value is varied
+    fn param_line(&self, variation: &Variation, idx: &mut usize) -> String {
+        let summary = self.class.param_summary();
+        match (self.tolerance, self.class.value_token_index()) {
+            (Some(tol), Some(vi)) => {
+                let mut tokens: Vec<String> =
+                    summary.split_whitespace().map(|s| s.to_string()).collect();
+                // units are not accepted within the expression of the varied value
+                if let Some(v) = self
+                    .class
+                    .value_unit()
+                    .and_then(|u| eng::parse(&tokens[vi], Some(u)).ok())
+                {
+                    tokens[vi] = eng::format_exact(v);
+                }
+                tokens[vi] = variation.apply(&tokens[vi], tol, *idx);
+                *idx += 1;
+                tokens.join(" ")
+            }
+            _ => summary,
+        }
+    }
     /// returns a reference to the device class
     pub fn class(&self) -> &DeviceClass {
         &self.class
@@ -110,8 +159,11 @@ impl Device {
             interactable: Interactable::new(class.graphics().bounds().cast().cast_unit()),
             transform: SSTransform::identity(),
             class,
+            tolerance: None,
+            expr_value: None,
             connected_nets: vec![],
             op: vec![],
+            op_currents: vec![],
         }
     }
     /// returns the schematic coordiantes of the devices ports in order
@@ -123,6 +175,14 @@ impl Device {
             .map(|p| self.transform.transform_point(p.offset))
             .collect()
     }
+    /// returns the index of the port at ssp, if any
+    pub fn port_at(&self, ssp: SSPoint) -> Option<usize> {
+        self.class
+            .graphics()
+            .ports()
+            .iter()
+            .position(|p| self.transform.transform_point(p.offset) == ssp)
+    }
     /// returns true if any port occupies ssp
     pub fn ports_occupy_ssp(&self, ssp: SSPoint) -> bool {
         for p in self.class.graphics().ports() {
@@ -146,8 +206,9 @@ impl Device {
             .cast()
             .cast_unit();
     }
-    /// returns the device's spice netlist line
-    pub fn spice_line(&mut self, nets: &Nets) -> String {
+    /// returns the device's spice netlist line. Device values with a tolerance are varied according to variation,
+    /// idx is the index of the next varied value in the netlist
+    pub fn spice_line(&mut self, nets: &Nets, variation: &Variation, idx: &mut usize) -> String {
         self.connected_nets.clear();
         let mut sline = self.id.ng_id();
         sline.push(' ');
@@ -163,21 +224,54 @@ impl Device {
             sline.push(' ');
             self.connected_nets.push(net_name);
         }
-        sline.push_str(&self.class.param_summary());
+        sline.push_str(&self.param_line(variation, idx));
         sline.push('\n');
         sline
     }
+    /// clear the operating point of the device, e.g. after a failed simulation
+    pub fn clear_op(&mut self) {
+        self.op.clear();
+        self.op_currents.clear();
+    }
+    /// sets the operating point currents into the device ports, in order of device ports
+    pub fn set_op_currents(&mut self, currents: Vec<Option<f64>>) {
+        self.op_currents = currents;
+    }
+    /// returns the operating point current into port
+    pub fn op_current_at_port(&self, port: usize) -> Option<f64> {
+        self.op_currents.get(port).copied().flatten()
+    }
+    /// returns the operating point current into the first port, e.g. through a resistor or into a drain
+    pub fn op_current(&self) -> Option<f64> {
+        self.op_currents.first().copied().flatten()
+    }
+    /// returns the power dissipated by the device at the operating point, negative if the device delivers power
+    pub fn op_power(&self) -> Option<f64> {
+        if self.op.len() != self.op_currents.len() || self.op.is_empty() {
+            return None;
+        }
+        self.op
+            .iter()
+            .zip(&self.op_currents)
+            .map(|(v, i)| Some(v.as_ref()?.re as f64 * (*i)?))
+            .sum()
+    }
     /// fill in the operating point for the device
     pub fn op(&mut self, pkvecvaluesall: &paprika::PkVecvaluesall) {
         self.op.clear();
+        self.op_currents.clear();
         for n in &self.connected_nets {
-            for v in &pkvecvaluesall.vecsa {
-                if &v.name == n {
-                    let pkval = (*v.as_ref()).clone();
-                    self.op.push(pkval.into());
-                    break;
-                }
-            }
+            // ngspice sends no vector for ground
+            let v = if n == "0" {
+                Some(num::Complex::new(0.0, 0.0))
+            } else {
+                pkvecvaluesall
+                    .vecsa
+                    .iter()
+                    .find(|v| &v.name == n)
+                    .map(|v| (*v.as_ref()).clone().into())
+            };
+            self.op.push(v);
         }
     }
 }
@@ -196,17 +290,76 @@ impl Drawable for Device {
         };
         frame.fill_text(a);
 
+        let mut summary = self.class.param_summary();
+        let param_error = self.class.param_error();
+        match (&self.expr_value, &param_error) {
+            (_, Some(e)) | (Some(Err(e)), _) => summary.push_str(&format!(" ({})", e)),
+            (Some(Ok(v)), None) => summary.push_str(&format!(" = {}", v)),
+            (None, None) => {}
+        }
+        if let Some(tol) = self.tolerance {
+            summary.push_str(&format!(" ±{}%", tol * 100.0));
+        }
         let b = Text {
-            content: self.class.param_summary(),
+            content: summary,
             position: Point::from(vct_c.transform_point(VSPoint::new(1.0, 0.0))).into(),
-            color: Color::from_rgba(0.5, 1.0, 1.0, 1.0),
+            color: match (&self.expr_value, param_error) {
+                (Some(Err(_)), _) | (_, Some(_)) => Color::from_rgba(1.0, 0.3, 0.3, 1.0),
+                _ => Color::from_rgba(0.5, 1.0, 1.0, 1.0),
+            },
             size: vcscale,
             ..Default::default()
         };
         frame.fill_text(b);
 
         let ports = self.class.graphics().ports();
-        for (i, v) in self.op.iter().enumerate() {
+        if let (Some(i), Some(port)) = (self.op_current(), ports.first()) {
+            let op_color = Color::from_rgba(0.6, 1.0, 0.6, 1.0);
+            let mut content = format!("{}A", eng::format(i));
+            if let Some(p) = self.op_power() {
+                content.push_str(&format!(" {}W", eng::format(p)));
+            }
+            let c = Text {
+                content,
+                position: Point::from(vct_c.transform_point(VSPoint::new(1.0, -1.0))).into(),
+                color: op_color,
+                size: vcscale,
+                ..Default::default()
+            };
+            frame.fill_text(c);
+            // arrow along the first port towards the device, pointing in the direction of current flow
+            let p0: VSPoint = port.offset.cast().cast_unit();
+            let dir = (VSPoint::origin() - p0).normalize();
+            if dir.x.is_finite() && dir.y.is_finite() {
+                let (tail, head) = (p0 + dir * 0.3, p0 + dir * 1.0);
+                let (tail, head) = if i >= 0.0 { (tail, head) } else { (head, tail) };
+                let fwd = (head - tail).normalize() * 0.3;
+                let side = VSVec::new(-fwd.y, fwd.x);
+                let to_c =
+                    |p: VSPoint| -> iced::Point { Point::from(vct_c.transform_point(p)).into() };
+                let path = Path::new(|b| {
+                    b.move_to(to_c(tail));
+                    b.line_to(to_c(head));
+                    b.move_to(to_c(head - fwd + side));
+                    b.line_to(to_c(head));
+                    b.line_to(to_c(head - fwd - side));
+                });
+                frame.stroke(
+                    &path,
+                    Stroke {
+                        width: 1.5,
+                        style: stroke::Style::Solid(op_color),
+                        ..Stroke::default()
+                    },
+                );
+            }
+        }
+        for (i, v) in self
+            .op
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| Some((i, v.as_ref()?)))
+        {
             let b = Text {
                 content: if v.im != 0.0 {
                     format! {"{:+.2e}", v}
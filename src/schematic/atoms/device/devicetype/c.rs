@@ -6,11 +6,14 @@
 use super::super::params;
 use super::{Graphics, Port};
 use crate::schematic::atoms::CirArc;
+use crate::schematic::eng;
 use crate::schematic::interactable::Interactable;
 use crate::transforms::{SSBox, SSPoint, VSPoint};
 use lazy_static::lazy_static;
 
 pub const ID_PREFIX: &str = "C";
+/// unit accepted after the capacitance value
+pub const UNIT: &str = "F";
 
 lazy_static! {
     static ref DEFAULT_GRAPHICS: Graphics = Graphics {
@@ -43,18 +46,28 @@ lazy_static! {
 #[derive(Debug, Clone)]
 pub enum ParamC {
     Raw(params::Raw),
+    /// specify the capacitance only
+    Value(params::SingleValue),
 }
 impl Default for ParamC {
     fn default() -> Self {
-        ParamC::Raw(params::Raw::new(String::from("10p")))
+        ParamC::Value(params::SingleValue::new(10e-12, UNIT))
     }
 }
 impl ParamC {
     pub fn summary(&self) -> String {
         match self {
             ParamC::Raw(s) => s.raw.clone(),
+            ParamC::Value(v) => v.summary(),
         }
     }
+    /// sets the parameters from the spice line (after port connects). A lone valid value is stored as a value, anything else as raw
+    pub fn set(&mut self, new: String) {
+        *self = match eng::parse(&new, Some(UNIT)) {
+            Ok(v) => ParamC::Value(params::SingleValue::new(v, UNIT)),
+            Err(_) => ParamC::Raw(params::Raw::new(new)),
+        };
+    }
 }
 
 #[derive(Debug, Clone)]
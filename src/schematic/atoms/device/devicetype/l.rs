@@ -7,11 +7,14 @@
 use super::super::params;
 use super::{Graphics, Port};
 use crate::schematic::atoms::CirArc;
+use crate::schematic::eng;
 use crate::schematic::interactable::Interactable;
 use crate::transforms::{SSBox, SSPoint, VSPoint};
 use lazy_static::lazy_static;
 
 pub const ID_PREFIX: &str = "L";
+/// unit accepted after the inductance value
+pub const UNIT: &str = "H";
 
 lazy_static! {
     static ref DEFAULT_GRAPHICS: Graphics = Graphics {
@@ -65,18 +68,28 @@ lazy_static! {
 #[derive(Debug, Clone)]
 pub enum Param {
     Raw(params::Raw),
+    /// specify the inductance only
+    Value(params::SingleValue),
 }
 impl Default for Param {
     fn default() -> Self {
-        Param::Raw(params::Raw::new(String::from("1m")))
+        Param::Value(params::SingleValue::new(1e-3, UNIT))
     }
 }
 impl Param {
     pub fn summary(&self) -> String {
         match self {
             Param::Raw(s) => s.raw.clone(),
+            Param::Value(v) => v.summary(),
         }
     }
+    /// sets the parameters from the spice line (after port connects). A lone valid value is stored as a value, anything else as raw
+    pub fn set(&mut self, new: String) {
+        *self = match eng::parse(&new, Some(UNIT)) {
+            Ok(v) => Param::Value(params::SingleValue::new(v, UNIT)),
+            Err(_) => Param::Raw(params::Raw::new(new)),
+        };
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,6 +1,8 @@
 //! device type. Resistors are a distinct type from capacitors, etc.
 
+use super::params;
 use crate::schematic::atoms::Port;
+use crate::schematic::eng;
 use crate::transforms::{Point, SSBox, VCTransform, VSPoint};
 use crate::{schematic::atoms::CirArc, Drawable};
 use iced::{
@@ -147,15 +149,9 @@ impl DeviceClass {
             DeviceClass::Nm(x) => match &mut x.params {
                 nmos::Param::Raw(y) => y.set(new),
             },
-            DeviceClass::R(x) => match &mut x.params {
-                r::Param::Raw(y) => y.set(new),
-            },
-            DeviceClass::L(x) => match &mut x.params {
-                l::Param::Raw(y) => y.set(new),
-            },
-            DeviceClass::C(x) => match &mut x.params {
-                c::ParamC::Raw(y) => y.set(new),
-            },
+            DeviceClass::R(x) => x.params.set(new),
+            DeviceClass::L(x) => x.params.set(new),
+            DeviceClass::C(x) => x.params.set(new),
             DeviceClass::Gnd(_) => {}
             DeviceClass::V(x) => match &mut x.params {
                 v::Param::Raw(y) => y.set(new),
@@ -196,6 +192,80 @@ impl DeviceClass {
             DeviceClass::D(x) => x.params.summary(),
         }
     }
+    /// returns an error describing why new is not valid as the parameters of the device. Only device values are checked
+    pub fn validate_param(&self, new: &str) -> Result<(), String> {
+        match self {
+            DeviceClass::R(_) => params::validate_value(new, r::UNIT),
+            DeviceClass::L(_) => params::validate_value(new, l::UNIT),
+            DeviceClass::C(_) => params::validate_value(new, c::UNIT),
+            _ => Ok(()),
+        }
+    }
+    /// returns the unit of the device value, if the device is a resistor, inductor or capacitor
+    pub fn value_unit(&self) -> Option<&'static str> {
+        match self {
+            DeviceClass::R(_) => Some(r::UNIT),
+            DeviceClass::L(_) => Some(l::UNIT),
+            DeviceClass::C(_) => Some(c::UNIT),
+            _ => None,
+        }
+    }
+    /// returns the value parsed from s if the device is a resistor or capacitor, the passives which come in standard values
+    pub fn passive_value(&self, s: &str) -> Option<f64> {
+        match self {
+            DeviceClass::R(_) => eng::parse(s, Some(r::UNIT)).ok(),
+            DeviceClass::C(_) => eng::parse(s, Some(c::UNIT)).ok(),
+            _ => None,
+        }
+    }
+    /// returns the error of the current device parameters, if they are not valid
+    pub fn param_error(&self) -> Option<String> {
+        self.validate_param(&self.param_summary()).err()
+    }
+    /// returns the index of the token holding the device value within the parameter summary, if the device has a value
+    /// which can be given a tolerance: the resistance, inductance, capacitance or dc value of a source
+    pub fn value_token_index(&self) -> Option<usize> {
+        let summary = self.param_summary();
+        let first = summary.split_whitespace().next()?;
+        match self {
+            DeviceClass::R(_) | DeviceClass::L(_) | DeviceClass::C(_) => Some(0),
+            DeviceClass::V(_) | DeviceClass::I(_) => {
+                if first.eq_ignore_ascii_case("dc") {
+                    Some(1)
+                } else if first.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c)) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+    /// returns the ngspice vector of the current into port of the device with netlist identifier id,
+    /// the name of the terminal and the sign the vector takes. Returns None if the current is not available
+    pub fn terminal_current(&self, id: &str, port: usize) -> Option<(String, &'static str, f64)> {
+        let id = id.to_lowercase();
+        // two terminal devices report the current flowing in at the first port
+        let (terminal, sign) = match port {
+            0 => ("p", 1.0),
+            1 => ("n", -1.0),
+            _ => ("", 0.0),
+        };
+        match self {
+            DeviceClass::Pm(_) | DeviceClass::Nm(_) => ["d", "g", "s", "b"]
+                .get(port)
+                .map(|t| (format!("@{}[i{}]", id, t), *t, 1.0)),
+            _ if port > 1 => None,
+            DeviceClass::V(_) | DeviceClass::Gnd(_) => {
+                Some((format!("{}#branch", id), terminal, sign))
+            }
+            DeviceClass::R(_) | DeviceClass::L(_) | DeviceClass::C(_) => {
+                Some((format!("@{}[i]", id), terminal, sign))
+            }
+            DeviceClass::I(_) => Some((format!("@{}[current]", id), terminal, sign)),
+            DeviceClass::D(_) => Some((format!("@{}[id]", id), terminal, sign)),
+        }
+    }
     /// returns the id prefix of the device class
     pub fn id_prefix(&self) -> &'static str {
         match self {
@@ -3,11 +3,14 @@
 use super::super::params;
 use super::Graphics;
 use crate::schematic::atoms::Port;
+use crate::schematic::eng;
 use crate::schematic::interactable::Interactable;
 use crate::transforms::{SSBox, SSPoint, VSPoint};
 use lazy_static::lazy_static;
 
 pub const ID_PREFIX: &str = "R";
+/// unit accepted after the resistance value
+pub const UNIT: &str = "ohm";
 
 lazy_static! {
     static ref DEFAULT_GRAPHICS: Graphics = Graphics {
@@ -46,18 +49,28 @@ lazy_static! {
 pub enum Param {
     /// specify the spice line directly (after id and port connections)
     Raw(params::Raw),
+    /// specify the resistance only
+    Value(params::SingleValue),
 }
 impl Default for Param {
     fn default() -> Self {
-        Param::Raw(params::Raw::new(String::from("1k")))
+        Param::Value(params::SingleValue::new(1e3, UNIT))
     }
 }
 impl Param {
     pub fn summary(&self) -> String {
         match self {
             Param::Raw(s) => s.raw.clone(),
+            Param::Value(v) => v.summary(),
         }
     }
+    /// sets the parameters from the spice line (after port connects). A lone valid value is stored as a value, anything else as raw
+    pub fn set(&mut self, new: String) {
+        *self = match eng::parse(&new, Some(UNIT)) {
+            Ok(v) => Param::Value(params::SingleValue::new(v, UNIT)),
+            Err(_) => Param::Raw(params::Raw::new(new)),
+        };
+    }
 }
 
 /// resistor device class
@@ -5,6 +5,7 @@ use std::{cell::RefCell, hash::Hasher, rc::Rc};
 pub mod deviceinstance;
 pub mod devicetype;
 mod params;
+pub use params::Variation;
 
 use deviceinstance::Device;
 
@@ -2,6 +2,7 @@
 //! multiple devices can use the same parameter specifier. e.g. all devices can use the `Raw` paramter specifier, R L C can use `SingleValue`, etc.
 //! a device should be able to choose between all compatible parameter specifier
 
+use crate::schematic::eng;
 use iced::{
     widget::{button, text_input},
     Element, Length,
@@ -51,3 +52,64 @@ impl Raw {
         self.raw = new;
     }
 }
+
+/// this struct to specify a device parameter as a single value, e.g. the resistance of a resistor
+#[derive(Debug, Clone)]
+pub struct SingleValue {
+    pub value: f64,
+    /// unit written after the value, e.g. `ohm`
+    unit: &'static str,
+}
+impl SingleValue {
+    pub fn new(value: f64, unit: &'static str) -> Self {
+        SingleValue { value, unit }
+    }
+    /// returns the value in engineering notation with its unit, keeping every digit, e.g. `4.7kohm`
+    pub fn summary(&self) -> String {
+        eng::format_unit(self.value, self.unit)
+    }
+}
+
+/// returns an error if the device value, the first token of the parameters, is neither a valid number with optional unit nor an expression
+pub fn validate_value(s: &str, unit: &str) -> Result<(), String> {
+    match s.split_whitespace().next() {
+        None => Err(String::from("missing value")),
+        Some(v) if v.starts_with('{') => Ok(()),
+        Some(v) => eng::parse(v, Some(unit)).map(|_| ()),
+    }
+}
+
+/// how device values with a tolerance are varied when creating the netlist
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Variation {
+    /// nominal values
+    #[default]
+    Nominal,
+    /// values randomized by ngspice every time the netlist is sourced, the tolerance taken as 3 sigma of a gaussian distribution
+    Random,
+    /// values at the edge of their tolerance band. Bit i of the mask selects the upper edge for the i-th varied value
+    Corner(u64),
+}
+
+impl Variation {
+    /// returns value varied by relative tolerance tol as a spice expression.
+    /// i is the index of the varied value within the netlist, used to pick its corner.
+    pub fn apply(&self, value: &str, tol: f32, i: usize) -> String {
+        // the value may itself be an expression
+        let expr = value
+            .strip_prefix('{')
+            .and_then(|v| v.strip_suffix('}'))
+            .unwrap_or(value);
+        match self {
+            Variation::Nominal => value.to_string(),
+            Variation::Random => format!("{{gauss({}, {}, 3)}}", expr, tol),
+            Variation::Corner(mask) => {
+                if mask.checked_shr(i as u32).unwrap_or(0) & 1 == 1 {
+                    format!("{{({})*{}}}", expr, 1.0 + tol)
+                } else {
+                    format!("{{({})*{}}}", expr, 1.0 - tol)
+                }
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ mod cirarc;
 mod device;
 mod lineseg;
 mod net_label;
+mod net_marker;
 mod nets;
 mod port;
+mod probe;
 
 pub use bounds::Bounds;
 pub use bounds::RcRBounds;
@@ -18,14 +20,19 @@ pub use lineseg::RcRLineSeg;
 pub use device::devicetype;
 pub use device::devicetype::DeviceClass;
 pub use device::RcRDevice;
+pub use device::Variation;
 
 pub use net_label::RcRLabel;
 
+pub use net_marker::{MarkerKind, NetMarker, RcRMarker};
+
 pub use nets::{NetEdge, NetVertex};
 
 pub use port::Port;
 pub use port::RcRPort;
 
+pub use probe::{Probe, ProbeKind, ProbeSignal, RcRProbe};
+
 pub use nets::PathWeight;
 
 use crate::transforms::VSBox;
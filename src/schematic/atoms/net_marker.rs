@@ -0,0 +1,205 @@
+//! net marker
+//! sets the initial condition (`.ic`) or operating point guess (`.nodeset`) of the net it is placed on
+
+use std::fmt::Display;
+use std::{cell::RefCell, hash::Hasher, rc::Rc};
+
+use crate::schematic::SchematicAtom;
+use crate::transforms::SSPoint;
+use crate::Drawable;
+use crate::{
+    schematic::interactable::{Interactable, Interactive},
+    transforms::{
+        sst_to_vvt, vvt_to_sst, Point, SSTransform, VCTransform, VSBox, VSPoint, VSVec, VVTransform,
+    },
+};
+use iced::{
+    widget::canvas::{self, Frame, Path, Text},
+    Color,
+};
+
+use by_address::ByAddress;
+
+/// what a net marker sets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarkerKind {
+    /// initial condition of transient analyses, `.ic`
+    #[default]
+    Ic,
+    /// initial guess for the operating point, `.nodeset`
+    Nodeset,
+}
+
+impl Display for MarkerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MarkerKind::Ic => "ic",
+                MarkerKind::Nodeset => "nodeset",
+            }
+        )
+    }
+}
+
+/// net marker, sets the voltage of the net it is placed on
+#[derive(Debug, Clone)]
+pub struct NetMarker {
+    /// what the marker sets
+    kind: MarkerKind,
+    /// net voltage, a spice number or `{expression}`
+    value: String,
+
+    /// marker interactable
+    pub interactable: Interactable,
+    /// marker transform - determines the posisiton and orientation of the marker in schematic space
+    transform: SSTransform,
+    /// interactive bounds before transform
+    bounds: VSBox,
+}
+
+impl NetMarker {
+    /// returns a new marker of kind, setting the net to 0V
+    pub fn new(kind: MarkerKind) -> Self {
+        let bounds = VSBox::from_points([
+            VSPoint::origin() - VSVec::new(0.5, 0.5),
+            VSPoint::origin() + VSVec::new(0.5, 0.5),
+        ]);
+        NetMarker {
+            kind,
+            value: String::from("0"),
+            interactable: Interactable { bounds },
+            transform: SSTransform::identity(),
+            bounds,
+        }
+    }
+    /// returns what the marker sets
+    pub fn kind(&self) -> MarkerKind {
+        self.kind
+    }
+    /// returns the net voltage set by the marker
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+    /// sets the net voltage
+    pub fn set_value(&mut self, value: String) {
+        self.value = value;
+    }
+    /// returns the `.ic` or `.nodeset` line setting net to the marker value
+    pub fn spice_line(&self, net: &str) -> String {
+        format!(".{} v({})={}\n", self.kind, net, self.value.trim())
+    }
+
+    /// returns the composite of the marker's transform and the given vct
+    fn compose_transform(&self, vct: VCTransform) -> VCTransform {
+        sst_to_vvt(self.transform).then(&vct)
+    }
+
+    pub fn pos(&self) -> SSPoint {
+        self.transform.transform_point(SSPoint::origin())
+    }
+
+    /// draws the marker, a diamond on the net followed by its value, in color
+    fn draw_with(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame, color: Color) {
+        let vct_c = self.compose_transform(vct);
+        let a = Text {
+            content: format!("{} {}", self.kind, self.value),
+            position: Point::from(vct_c.transform_point(VSPoint::new(0.25, 0.25))).into(),
+            color,
+            size: vcscale,
+            ..Default::default()
+        };
+        frame.fill_text(a);
+
+        let dim = 0.25;
+        let path = Path::new(|p| {
+            for (i, v) in [
+                VSVec::new(dim, 0.0),
+                VSVec::new(0.0, dim),
+                VSVec::new(-dim, 0.0),
+                VSVec::new(0.0, -dim),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let pt = Point::from(vct_c.transform_point(VSPoint::origin() + v)).into();
+                if i == 0 {
+                    p.move_to(pt);
+                } else {
+                    p.line_to(pt);
+                }
+            }
+            p.close();
+        });
+        let f = canvas::Fill {
+            style: canvas::Style::Solid(Color { a: 0.5, ..color }),
+            ..canvas::Fill::default()
+        };
+        frame.fill(&path, f);
+    }
+}
+
+impl Drawable for NetMarker {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        let color = match self.kind {
+            MarkerKind::Ic => Color::from_rgb(0.5, 1.0, 0.5),
+            MarkerKind::Nodeset => Color::from_rgb(0.7, 0.7, 1.0),
+        };
+        self.draw_with(vct, vcscale, frame, color);
+    }
+
+    fn draw_selected(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.draw_with(vct, vcscale, frame, Color::from_rgb(1.0, 0.8, 0.0));
+    }
+
+    fn draw_preview(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.draw_with(vct, vcscale, frame, Color::from_rgb(1.0, 1.0, 0.5));
+    }
+}
+
+impl Interactive for NetMarker {
+    fn transform(&mut self, vvt: VVTransform) {
+        let sst = vvt_to_sst(vvt);
+        self.transform = self.transform.then(&sst);
+        self.interactable.bounds = sst_to_vvt(self.transform).outer_transformed_box(&self.bounds);
+    }
+}
+
+/// newtype wrapper for `Rc<RefCell<NetMarker>>`. Hashes by memory address.
+#[derive(Debug, Clone)]
+pub struct RcRMarker(pub Rc<RefCell<NetMarker>>);
+impl PartialEq for RcRMarker {
+    fn eq(&self, other: &Self) -> bool {
+        ByAddress(self.0.clone()) == ByAddress(other.0.clone())
+    }
+}
+impl Eq for RcRMarker {}
+impl std::hash::Hash for RcRMarker {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ByAddress(self.0.clone()).hash(state);
+    }
+}
+
+impl Drawable for RcRMarker {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_persistent(vct, vcscale, frame);
+    }
+
+    fn draw_selected(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_selected(vct, vcscale, frame);
+    }
+
+    fn draw_preview(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_preview(vct, vcscale, frame);
+    }
+}
+
+impl SchematicAtom for RcRMarker {
+    fn contains_vsp(&self, vsp: VSPoint) -> bool {
+        self.0.borrow().interactable.contains_vsp(vsp)
+    }
+    fn bounding_box(&self) -> crate::transforms::VSBox {
+        self.0.borrow().interactable.bounds
+    }
+}
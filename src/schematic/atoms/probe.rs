@@ -0,0 +1,219 @@
+//! probe
+//! selects the signals saved by ngspice and plotted after a transient analysis
+
+use std::{cell::RefCell, hash::Hasher, rc::Rc};
+
+use crate::schematic::SchematicAtom;
+use crate::transforms::SSPoint;
+use crate::Drawable;
+use crate::{
+    schematic::interactable::{Interactable, Interactive},
+    transforms::{
+        sst_to_vvt, vvt_to_sst, Point, SSTransform, VCTransform, VSBox, VSPoint, VSVec, VVTransform,
+    },
+};
+use iced::{
+    widget::canvas::{stroke, Frame, LineCap, Path, Stroke, Text},
+    Color,
+};
+
+use by_address::ByAddress;
+
+/// what a probe measures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProbeKind {
+    /// voltage of the net at the tip
+    #[default]
+    Voltage,
+    /// voltage between the nets at the positive (first) and negative tip
+    Differential,
+    /// current into the device terminal at the tip
+    Current,
+}
+
+/// signal measured by a probe, resolved against the nets and devices of the schematic
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeSignal {
+    /// trace name, e.g. `v(net_1)`
+    pub name: String,
+    /// ngspice vector of the signal
+    pub vec: String,
+    /// ngspice vector subtracted from vec, for differential probes
+    pub ref_vec: Option<String>,
+    /// factor applied to the signal, -1 if the ngspice vector measures the opposite direction
+    pub sign: f64,
+}
+
+/// probe, placed with its tip(s) on a net or device terminal
+#[derive(Debug, Clone)]
+pub struct Probe {
+    /// what the probe measures
+    kind: ProbeKind,
+    /// name of the resolved signal, shown on canvas once the netlist is created
+    signal_name: Option<String>,
+
+    /// probe interactable
+    pub interactable: Interactable,
+    /// probe transform - determines the posisiton and orientation of the probe in schematic space
+    transform: SSTransform,
+    /// interactive bounds before transform
+    bounds: VSBox,
+}
+
+/// offset of the negative tip of differential probes
+const NEG_TIP: SSPoint = SSPoint::new(0, -2);
+
+impl Probe {
+    /// returns a new probe of kind
+    pub fn new(kind: ProbeKind) -> Self {
+        let bounds = match kind {
+            ProbeKind::Differential => VSBox::from_points([
+                VSPoint::new(-0.5, -2.5),
+                VSPoint::origin() + VSVec::new(0.5, 0.5),
+            ]),
+            _ => VSBox::from_points([
+                VSPoint::origin() - VSVec::new(0.5, 0.5),
+                VSPoint::origin() + VSVec::new(0.5, 0.5),
+            ]),
+        };
+        Probe {
+            kind,
+            signal_name: None,
+            interactable: Interactable { bounds },
+            transform: SSTransform::identity(),
+            bounds,
+        }
+    }
+    /// returns what the probe measures
+    pub fn kind(&self) -> ProbeKind {
+        self.kind
+    }
+    /// returns the positions of the probe tips, the positive tip first
+    pub fn tips(&self) -> Vec<SSPoint> {
+        let tips = match self.kind {
+            ProbeKind::Differential => vec![SSPoint::origin(), NEG_TIP],
+            _ => vec![SSPoint::origin()],
+        };
+        tips.into_iter()
+            .map(|p| self.transform.transform_point(p))
+            .collect()
+    }
+    /// sets the name of the resolved signal, None if the probe is not placed on a net or terminal
+    pub fn set_signal_name(&mut self, name: Option<String>) {
+        self.signal_name = name;
+    }
+
+    /// returns the composite of the probe's transform and the given vct
+    fn compose_transform(&self, vct: VCTransform) -> VCTransform {
+        sst_to_vvt(self.transform).then(&vct)
+    }
+
+    /// draws the probe tips and the signal name in color
+    fn draw_with(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame, color: Color) {
+        let vct_c = self.compose_transform(vct);
+        let probe_stroke = Stroke {
+            width: 2.0,
+            style: stroke::Style::Solid(color),
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+        let tips: Vec<VSPoint> = match self.kind {
+            ProbeKind::Differential => vec![VSPoint::origin(), NEG_TIP.cast().cast_unit()],
+            _ => vec![VSPoint::origin()],
+        };
+        for tip in &tips {
+            let c = Path::circle(
+                Point::from(vct_c.transform_point(*tip)).into(),
+                0.2 * vcscale,
+            );
+            frame.stroke(&c, probe_stroke.clone());
+        }
+        if let [pos, neg] = tips[..] {
+            let c = Path::line(
+                Point::from(vct_c.transform_point(pos)).into(),
+                Point::from(vct_c.transform_point(neg)).into(),
+            );
+            frame.stroke(&c, probe_stroke.clone());
+        }
+        let default_name = match self.kind {
+            ProbeKind::Voltage => "v",
+            ProbeKind::Differential => "v+ -",
+            ProbeKind::Current => "i",
+        };
+        let a = Text {
+            content: self
+                .signal_name
+                .clone()
+                .unwrap_or_else(|| default_name.to_string()),
+            position: Point::from(vct_c.transform_point(VSPoint::new(0.25, 0.25))).into(),
+            color,
+            size: vcscale,
+            ..Default::default()
+        };
+        frame.fill_text(a);
+    }
+}
+
+impl Drawable for Probe {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        let color = match self.kind {
+            ProbeKind::Current => Color::from_rgb(1.0, 0.6, 0.2),
+            _ => Color::from_rgb(1.0, 0.4, 0.8),
+        };
+        self.draw_with(vct, vcscale, frame, color);
+    }
+
+    fn draw_selected(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.draw_with(vct, vcscale, frame, Color::from_rgb(1.0, 0.8, 0.0));
+    }
+
+    fn draw_preview(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.draw_with(vct, vcscale, frame, Color::from_rgb(1.0, 1.0, 0.5));
+    }
+}
+
+impl Interactive for Probe {
+    fn transform(&mut self, vvt: VVTransform) {
+        let sst = vvt_to_sst(vvt);
+        self.transform = self.transform.then(&sst);
+        self.interactable.bounds = sst_to_vvt(self.transform).outer_transformed_box(&self.bounds);
+    }
+}
+
+/// newtype wrapper for `Rc<RefCell<Probe>>`. Hashes by memory address.
+#[derive(Debug, Clone)]
+pub struct RcRProbe(pub Rc<RefCell<Probe>>);
+impl PartialEq for RcRProbe {
+    fn eq(&self, other: &Self) -> bool {
+        ByAddress(self.0.clone()) == ByAddress(other.0.clone())
+    }
+}
+impl Eq for RcRProbe {}
+impl std::hash::Hash for RcRProbe {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ByAddress(self.0.clone()).hash(state);
+    }
+}
+
+impl Drawable for RcRProbe {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_persistent(vct, vcscale, frame);
+    }
+
+    fn draw_selected(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_selected(vct, vcscale, frame);
+    }
+
+    fn draw_preview(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        self.0.borrow().draw_preview(vct, vcscale, frame);
+    }
+}
+
+impl SchematicAtom for RcRProbe {
+    fn contains_vsp(&self, vsp: VSPoint) -> bool {
+        self.0.borrow().interactable.contains_vsp(vsp)
+    }
+    fn bounding_box(&self) -> crate::transforms::VSBox {
+        self.0.borrow().interactable.bounds
+    }
+}
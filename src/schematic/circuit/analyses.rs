@@ -0,0 +1,728 @@
+//! analysis setups
+//! holds the user specified parameters of the various ngspice analyses and generates the corresponding control lines
+
+use std::fmt::Display;
+
+use crate::analysis::trace::{Trace, TraceSource};
+use crate::schematic::eng;
+use crate::transforms::VSPoint;
+
+/// ngspice manual 15.3.1 - frequency variation of an ac small-signal analysis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AcSweep {
+    /// decade variation - points is the number of points per decade
+    #[default]
+    Dec,
+    /// octave variation - points is the number of points per octave
+    Oct,
+    /// linear variation - points is the total number of points
+    Lin,
+}
+
+impl AcSweep {
+    /// all variants, for use in pick lists
+    pub const ALL: [AcSweep; 3] = [AcSweep::Dec, AcSweep::Oct, AcSweep::Lin];
+}
+
+impl Display for AcSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AcSweep::Dec => "dec",
+                AcSweep::Oct => "oct",
+                AcSweep::Lin => "lin",
+            }
+        )
+    }
+}
+
+/// ac small-signal analysis setup
+/// `ac ( DEC | OCT | LIN ) N FSTART FSTOP`
+#[derive(Debug, Clone)]
+pub struct AcSetup {
+    /// frequency variation
+    pub sweep: AcSweep,
+    /// number of points, meaning depends on sweep
+    pub points: String,
+    /// starting frequency (hertz)
+    pub fstart: String,
+    /// final frequency (hertz)
+    pub fstop: String,
+}
+
+impl Default for AcSetup {
+    fn default() -> Self {
+        AcSetup {
+            sweep: AcSweep::default(),
+            points: String::from("10"),
+            fstart: String::from("1"),
+            fstop: String::from("100k"),
+        }
+    }
+}
+
+impl AcSetup {
+    /// returns the ngspice control line for this analysis
+    pub fn command(&self) -> String {
+        format!(
+            "ac {} {} {} {}",
+            self.sweep, self.points, self.fstart, self.fstop
+        )
+    }
+}
+
+/// a single sweep of a dc transfer curve analysis
+#[derive(Debug, Clone)]
+pub struct DcSweepSrc {
+    /// name of the swept independent source or resistor, or `temp` to sweep temperature
+    pub src: String,
+    /// starting value
+    pub start: String,
+    /// final value
+    pub stop: String,
+    /// increment value
+    pub step: String,
+}
+
+impl DcSweepSrc {
+    /// returns the sweep specification as used in the dc control line
+    fn spec(&self) -> String {
+        format!("{} {} {} {}", self.src, self.start, self.stop, self.step)
+    }
+}
+
+/// dc transfer curve analysis setup
+/// `dc SRCNAM VSTART VSTOP VINCR [ SRC2 START2 STOP2 INCR2 ]`
+#[derive(Debug, Clone)]
+pub struct DcSetup {
+    /// the inner sweep - becomes the x axis of the resulting curves
+    pub sweep1: DcSweepSrc,
+    /// the outer sweep - produces one curve per value. Ignored if its source is left empty
+    pub sweep2: DcSweepSrc,
+}
+
+impl Default for DcSetup {
+    fn default() -> Self {
+        DcSetup {
+            sweep1: DcSweepSrc {
+                src: String::from("V1"),
+                start: String::from("0"),
+                stop: String::from("5"),
+                step: String::from("0.1"),
+            },
+            sweep2: DcSweepSrc {
+                src: String::new(),
+                start: String::from("0"),
+                stop: String::from("5"),
+                step: String::from("1"),
+            },
+        }
+    }
+}
+
+impl DcSetup {
+    /// returns the ngspice control line for this analysis
+    pub fn command(&self) -> String {
+        if self.sweep2.src.trim().is_empty() {
+            format!("dc {}", self.sweep1.spec())
+        } else {
+            format!("dc {} {}", self.sweep1.spec(), self.sweep2.spec())
+        }
+    }
+}
+
+/// analyses whose results are presented as tables or as plots other than waveforms over a sweep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnalysisKind {
+    /// noise analysis
+    #[default]
+    Noise,
+    /// pole-zero analysis
+    Pz,
+    /// dc transfer function analysis
+    Tf,
+    /// dc sensitivity analysis
+    Sens,
+}
+
+impl AnalysisKind {
+    /// all variants, for use in pick lists
+    pub const ALL: [AnalysisKind; 4] = [
+        AnalysisKind::Noise,
+        AnalysisKind::Pz,
+        AnalysisKind::Tf,
+        AnalysisKind::Sens,
+    ];
+}
+
+impl Display for AnalysisKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AnalysisKind::Noise => "noise",
+                AnalysisKind::Pz => "pz",
+                AnalysisKind::Tf => "tf",
+                AnalysisKind::Sens => "sens",
+            }
+        )
+    }
+}
+
+/// common interface of analysis setups, used to build their setup dialogs
+pub trait AnalysisSetup {
+    /// returns the label and value of every setup field, in order
+    fn fields(&self) -> Vec<(&'static str, &str)>;
+    /// sets the value of the field at index i
+    fn set_field(&mut self, i: usize, value: String);
+    /// returns the ngspice control line for this analysis
+    fn command(&self) -> String;
+}
+
+/// noise analysis setup
+/// `noise V(OUTPUT <,REF>) SRC ( DEC | LIN | OCT ) PTS FSTART FSTOP`
+#[derive(Debug, Clone)]
+pub struct NoiseSetup {
+    /// output voltage, e.g. `v(out)` or `v(out, ref)`
+    pub output: String,
+    /// independent source to which the input noise is referred
+    pub src: String,
+    /// frequency variation: dec, oct or lin
+    pub sweep: String,
+    /// number of points, meaning depends on sweep
+    pub points: String,
+    /// starting frequency (hertz)
+    pub fstart: String,
+    /// final frequency (hertz)
+    pub fstop: String,
+}
+
+impl Default for NoiseSetup {
+    fn default() -> Self {
+        NoiseSetup {
+            output: String::from("v(net_0)"),
+            src: String::from("V1"),
+            sweep: String::from("dec"),
+            points: String::from("10"),
+            fstart: String::from("1"),
+            fstop: String::from("100k"),
+        }
+    }
+}
+
+impl AnalysisSetup for NoiseSetup {
+    fn fields(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("output", &self.output),
+            ("source", &self.src),
+            ("dec/oct/lin", &self.sweep),
+            ("points", &self.points),
+            ("fstart", &self.fstart),
+            ("fstop", &self.fstop),
+        ]
+    }
+    fn set_field(&mut self, i: usize, value: String) {
+        match i {
+            0 => self.output = value,
+            1 => self.src = value,
+            2 => self.sweep = value,
+            3 => self.points = value,
+            4 => self.fstart = value,
+            5 => self.fstop = value,
+            _ => {}
+        }
+    }
+    fn command(&self) -> String {
+        format!(
+            "noise {} {} {} {} {} {}",
+            self.output, self.src, self.sweep, self.points, self.fstart, self.fstop
+        )
+    }
+}
+
+/// pole-zero analysis setup
+/// `pz NODE1 NODE2 NODE3 NODE4 ( CUR | VOL ) ( POL | ZER | PZ )`
+#[derive(Debug, Clone)]
+pub struct PzSetup {
+    /// positive input node
+    pub in_pos: String,
+    /// negative input node
+    pub in_neg: String,
+    /// positive output node
+    pub out_pos: String,
+    /// negative output node
+    pub out_neg: String,
+    /// transfer function type: cur (transimpedance) or vol (voltage gain)
+    pub transfer: String,
+    /// what to solve for: pol, zer or pz
+    pub solve: String,
+}
+
+impl Default for PzSetup {
+    fn default() -> Self {
+        PzSetup {
+            in_pos: String::from("net_0"),
+            in_neg: String::from("0"),
+            out_pos: String::from("net_1"),
+            out_neg: String::from("0"),
+            transfer: String::from("vol"),
+            solve: String::from("pz"),
+        }
+    }
+}
+
+impl AnalysisSetup for PzSetup {
+    fn fields(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("in +", &self.in_pos),
+            ("in -", &self.in_neg),
+            ("out +", &self.out_pos),
+            ("out -", &self.out_neg),
+            ("cur/vol", &self.transfer),
+            ("pol/zer/pz", &self.solve),
+        ]
+    }
+    fn set_field(&mut self, i: usize, value: String) {
+        match i {
+            0 => self.in_pos = value,
+            1 => self.in_neg = value,
+            2 => self.out_pos = value,
+            3 => self.out_neg = value,
+            4 => self.transfer = value,
+            5 => self.solve = value,
+            _ => {}
+        }
+    }
+    fn command(&self) -> String {
+        format!(
+            "pz {} {} {} {} {} {}",
+            self.in_pos, self.in_neg, self.out_pos, self.out_neg, self.transfer, self.solve
+        )
+    }
+}
+
+/// dc transfer function analysis setup
+/// `tf OUTVAR INSRC`
+#[derive(Debug, Clone)]
+pub struct TfSetup {
+    /// output variable, e.g. `v(out)` or `i(vload)`
+    pub output: String,
+    /// input source
+    pub src: String,
+}
+
+impl Default for TfSetup {
+    fn default() -> Self {
+        TfSetup {
+            output: String::from("v(net_0)"),
+            src: String::from("V1"),
+        }
+    }
+}
+
+impl AnalysisSetup for TfSetup {
+    fn fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("output", &self.output), ("source", &self.src)]
+    }
+    fn set_field(&mut self, i: usize, value: String) {
+        match i {
+            0 => self.output = value,
+            1 => self.src = value,
+            _ => {}
+        }
+    }
+    fn command(&self) -> String {
+        format!("tf {} {}", self.output, self.src)
+    }
+}
+
+/// dc sensitivity analysis setup
+/// `sens OUTVAR`
+#[derive(Debug, Clone)]
+pub struct SensSetup {
+    /// output variable, e.g. `v(out)` or `i(vload)`
+    pub output: String,
+}
+
+impl Default for SensSetup {
+    fn default() -> Self {
+        SensSetup {
+            output: String::from("v(net_0)"),
+        }
+    }
+}
+
+impl AnalysisSetup for SensSetup {
+    fn fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("output", &self.output)]
+    }
+    fn set_field(&mut self, i: usize, value: String) {
+        if i == 0 {
+            self.output = value;
+        }
+    }
+    fn command(&self) -> String {
+        format!("sens {}", self.output)
+    }
+}
+
+/// a row of an analysis result table
+#[derive(Debug, Clone)]
+pub struct ResultRow {
+    /// name of the result, e.g. the ngspice vector name
+    pub name: String,
+    /// formatted value of the result
+    pub value: String,
+    /// netlist identifier of the device the result relates to, if any
+    pub device: Option<String>,
+}
+
+/// current and power of a device at the operating point
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceOp {
+    /// netlist identifier of the device
+    pub id: String,
+    /// current into the first port of the device
+    pub current: Option<f64>,
+    /// power dissipated by the device, negative if the device delivers power
+    pub power: Option<f64>,
+}
+
+/// how the device currents and powers of the operating point are ordered in the result table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpSort {
+    /// by netlist identifier
+    Device,
+    /// by magnitude of current, largest first
+    Current,
+    /// by magnitude of power, largest first
+    #[default]
+    Power,
+}
+
+impl OpSort {
+    /// all variants, for use in pick lists
+    pub const ALL: [OpSort; 3] = [OpSort::Device, OpSort::Current, OpSort::Power];
+}
+
+impl Display for OpSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OpSort::Device => "device",
+                OpSort::Current => "current",
+                OpSort::Power => "power",
+            }
+        )
+    }
+}
+
+/// returns the device currents and powers as result table rows, ordered by sort. Unknown values sort last
+pub fn device_op_rows(ops: &[DeviceOp], sort: OpSort) -> Vec<ResultRow> {
+    let mut ops: Vec<&DeviceOp> = ops.iter().collect();
+    let key = |v: Option<f64>| v.map_or(-1.0, f64::abs);
+    match sort {
+        OpSort::Device => ops.sort_by(|a, b| a.id.cmp(&b.id)),
+        OpSort::Current => ops.sort_by(|a, b| key(b.current).total_cmp(&key(a.current))),
+        OpSort::Power => ops.sort_by(|a, b| key(b.power).total_cmp(&key(a.power))),
+    }
+    let fmt = |v: Option<f64>, unit: &str| {
+        v.map_or_else(
+            || "-".to_string(),
+            |v| format!("{}{}", eng::format(v), unit),
+        )
+    };
+    ops.into_iter()
+        .map(|op| ResultRow {
+            name: op.id.clone(),
+            value: format!("I={} P={}", fmt(op.current, "A"), fmt(op.power, "W")),
+            device: Some(op.id.clone()),
+        })
+        .collect()
+}
+
+/// how device and model values are varied between the runs of a monte carlo analysis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum McMode {
+    /// every value is drawn from a gaussian distribution, with its tolerance at 3 sigma
+    #[default]
+    Random,
+    /// every combination of values at the limits of their tolerance
+    Corners,
+}
+
+impl McMode {
+    /// all variants, for use in pick lists
+    pub const ALL: [McMode; 2] = [McMode::Random, McMode::Corners];
+}
+
+impl Display for McMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                McMode::Random => "random",
+                McMode::Corners => "corners",
+            }
+        )
+    }
+}
+
+/// analyses which can be repeated over multiple runs, with their traces overlaid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunAnalysisKind {
+    /// transient analysis
+    #[default]
+    Tran,
+    /// ac small-signal analysis
+    Ac,
+    /// dc transfer curve analysis
+    Dc,
+}
+
+impl RunAnalysisKind {
+    /// all variants, for use in pick lists
+    pub const ALL: [RunAnalysisKind; 3] = [
+        RunAnalysisKind::Tran,
+        RunAnalysisKind::Ac,
+        RunAnalysisKind::Dc,
+    ];
+}
+
+impl Display for RunAnalysisKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RunAnalysisKind::Tran => "tran",
+                RunAnalysisKind::Ac => "ac",
+                RunAnalysisKind::Dc => "dc",
+            }
+        )
+    }
+}
+
+/// monte carlo and corner analysis setup
+#[derive(Debug, Clone)]
+pub struct McSetup {
+    /// random or corner variation
+    pub mode: McMode,
+    /// analysis run for every variation
+    pub analysis: RunAnalysisKind,
+    /// number of runs in random mode
+    pub runs: String,
+    /// measurement taken after every run, in the form of a `meas` statement without the analysis type,
+    /// e.g. `vmax max v(net_1)`. The first token names the measured result
+    pub meas: String,
+    /// model parameter variations, in the form `MODEL param=nominal tol%` separated by `;`
+    pub models: String,
+}
+
+impl Default for McSetup {
+    fn default() -> Self {
+        McSetup {
+            mode: McMode::default(),
+            analysis: RunAnalysisKind::default(),
+            runs: String::from("20"),
+            meas: String::new(),
+            models: String::new(),
+        }
+    }
+}
+
+impl McSetup {
+    /// returns the ngspice control line measuring the result of a run, if a measurement is set
+    pub fn meas_command(&self) -> Option<String> {
+        if self.meas.trim().is_empty() {
+            None
+        } else {
+            Some(format!("meas {} {}", self.analysis, self.meas))
+        }
+    }
+    /// returns the name of the vector holding the measured result, if a measurement is set
+    pub fn meas_name(&self) -> Option<&str> {
+        self.meas.split_whitespace().next()
+    }
+    /// returns the number of runs in random mode, or an error if it is not a positive integer
+    pub fn run_count(&self) -> Result<usize, String> {
+        match self.runs.trim().parse::<usize>() {
+            Ok(runs) if runs > 0 => Ok(runs),
+            _ => Err(format!("invalid number of runs: {}", self.runs.trim())),
+        }
+    }
+}
+
+/// parses a device tolerance entered in percent, with or without a trailing `%`, into a relative tolerance.
+/// An empty entry is no tolerance
+pub fn parse_tolerance(s: &str) -> Result<Option<f32>, String> {
+    let s = s.trim().trim_end_matches('%').trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    match s.parse::<f32>() {
+        Ok(t) if t.is_finite() && t >= 0.0 => Ok(Some(t / 100.0)),
+        _ => Err(format!("invalid tolerance: {}", s)),
+    }
+}
+
+/// returns the mean and standard deviation of values as result table rows
+pub fn mc_stats(name: &str, values: &[f64]) -> Vec<ResultRow> {
+    if values.is_empty() {
+        return vec![];
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    vec![
+        ResultRow {
+            name: format!("{} mean", name),
+            value: format!("{:.4e}", mean),
+            device: None,
+        },
+        ResultRow {
+            name: format!("{} std", name),
+            value: format!("{:.4e}", std),
+            device: None,
+        },
+    ]
+}
+
+/// returns the histogram of values as a trace outlining its bins, the count of each bin over the measured value
+pub fn histogram(name: &str, values: &[f64]) -> Option<Trace> {
+    const BINS: usize = 10;
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if values.is_empty() || !min.is_finite() || !max.is_finite() {
+        return None;
+    }
+    // values all alike fill a single bin of arbitrary width
+    let width = if max > min {
+        (max - min) / BINS as f64
+    } else {
+        min.abs().max(1.0) * 1e-3
+    };
+    let mut counts = [0usize; BINS];
+    for v in values {
+        counts[(((v - min) / width) as usize).min(BINS - 1)] += 1;
+    }
+    let bins = if max > min { BINS } else { 1 };
+    let mut points = vec![VSPoint::new(min as f32, 0.0)];
+    for (i, count) in counts.iter().take(bins).enumerate() {
+        let x0 = (min + width * i as f64) as f32;
+        let x1 = (min + width * (i + 1) as f64) as f32;
+        points.push(VSPoint::new(x0, *count as f32));
+        points.push(VSPoint::new(x1, *count as f32));
+        points.push(VSPoint::new(x1, 0.0));
+    }
+    Some(Trace::new(
+        format!("histogram of {}", name),
+        "",
+        "",
+        TraceSource::Mc,
+        points,
+    ))
+}
+
+/// how the values of a parameter step are specified
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StepSweep {
+    /// a list of values, e.g. `1k 2.2k 4.7k`
+    #[default]
+    List,
+    /// linear range - `START STOP INCR`
+    Lin,
+    /// logarithmic range - `START STOP POINTS` with points per decade
+    Dec,
+}
+
+impl StepSweep {
+    /// all variants, for use in pick lists
+    pub const ALL: [StepSweep; 3] = [StepSweep::List, StepSweep::Lin, StepSweep::Dec];
+}
+
+impl Display for StepSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                StepSweep::List => "list",
+                StepSweep::Lin => "lin",
+                StepSweep::Dec => "dec",
+            }
+        )
+    }
+}
+
+/// parameter stepping setup, similar to `.step` of other simulators
+#[derive(Debug, Clone)]
+pub struct StepSetup {
+    /// stepped quantity: a device, e.g. `R1`, a device parameter, e.g. `@m1[w]`, or a `.param`
+    pub target: String,
+    /// how values are specified
+    pub sweep: StepSweep,
+    /// the step values, see [`StepSweep`]
+    pub values: String,
+    /// analysis run for every step
+    pub analysis: RunAnalysisKind,
+}
+
+impl Default for StepSetup {
+    fn default() -> Self {
+        StepSetup {
+            target: String::from("R1"),
+            sweep: StepSweep::default(),
+            values: String::from("1k 2k 5k"),
+            analysis: RunAnalysisKind::default(),
+        }
+    }
+}
+
+impl StepSetup {
+    /// returns the step values, or an error describing the malformed specification
+    pub fn values(&self) -> Result<Vec<String>, String> {
+        /// maximum number of steps of a range, guards against typos such as a zero increment
+        const MAX_STEPS: usize = 1000;
+        if self.sweep == StepSweep::List {
+            return Ok(self.values.split_whitespace().map(String::from).collect());
+        }
+        let nums: Vec<f64> = self
+            .values
+            .split_whitespace()
+            .map(|s| eng::parse(s, None))
+            .collect::<Result<_, _>>()?;
+        let [start, stop, incr] = nums[..] else {
+            return Err(format!(
+                "expected START STOP {}",
+                if self.sweep == StepSweep::Lin {
+                    "INCR"
+                } else {
+                    "POINTS"
+                }
+            ));
+        };
+        let values: Vec<f64> = match self.sweep {
+            StepSweep::Lin if incr != 0.0 && (stop - start) / incr >= 0.0 => {
+                let n = ((stop - start) / incr + 1e-9).floor() as usize + 1;
+                (0..n.min(MAX_STEPS))
+                    .map(|i| start + incr * i as f64)
+                    .collect()
+            }
+            StepSweep::Dec if start > 0.0 && stop > start && incr >= 1.0 => {
+                let n = ((stop / start).log10() * incr + 1e-9).floor() as usize + 1;
+                (0..n.min(MAX_STEPS))
+                    .map(|i| start * 10f64.powf(i as f64 / incr))
+                    .collect()
+            }
+            _ => return Err(format!("invalid {} range: {}", self.sweep, self.values)),
+        };
+        Ok(values.into_iter().map(eng::format).collect())
+    }
+}
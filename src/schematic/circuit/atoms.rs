@@ -4,6 +4,8 @@
 use crate::schematic::atoms::NetEdge;
 use crate::schematic::atoms::RcRDevice;
 use crate::schematic::atoms::RcRLabel;
+use crate::schematic::atoms::RcRMarker;
+use crate::schematic::atoms::RcRProbe;
 use crate::transforms::VSBox;
 
 use enum_dispatch::enum_dispatch;
@@ -19,4 +21,6 @@ pub enum CircuitAtom {
     NetEdge,
     RcRDevice,
     RcRLabel,
+    RcRMarker,
+    RcRProbe,
 }
@@ -0,0 +1,137 @@
+//! simulation console
+//! keeps the ngspice output stream and the commands sent to ngspice, grouped by simulation run
+
+use std::fmt::Display;
+
+/// maximum number of runs kept, older runs are discarded
+const MAX_RUNS: usize = 20;
+/// maximum number of lines kept per run, later lines are discarded
+const MAX_LINES: usize = 10_000;
+
+/// origin of a console line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleSrc {
+    /// ngspice standard output
+    Stdout,
+    /// ngspice standard error
+    Stderr,
+    /// ngspice simulation status
+    Stat,
+    /// command sent to ngspice
+    Command,
+}
+
+/// a line of console output
+#[derive(Debug, Clone)]
+pub struct ConsoleLine {
+    pub src: ConsoleSrc,
+    pub text: String,
+}
+
+/// console output of a single simulation run, from sourcing the netlist until the next run
+#[derive(Debug, Clone)]
+pub struct ConsoleRun {
+    /// number of the run since startup
+    pub id: usize,
+    pub lines: Vec<ConsoleLine>,
+}
+
+/// console output of the most recent runs
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    runs: Vec<ConsoleRun>,
+    /// number of runs since startup
+    count: usize,
+}
+
+impl Console {
+    /// starts a new run, subsequent lines are added to it
+    pub fn begin_run(&mut self) {
+        self.count += 1;
+        self.runs.push(ConsoleRun {
+            id: self.count,
+            lines: vec![],
+        });
+        if self.runs.len() > MAX_RUNS {
+            self.runs.remove(0);
+        }
+    }
+    /// adds a line to the current run. Consecutive status lines replace one another, as ngspice reports progress through them
+    pub fn push(&mut self, src: ConsoleSrc, text: String) {
+        if self.runs.is_empty() {
+            self.begin_run();
+        }
+        let lines = &mut self.runs.last_mut().unwrap().lines;
+        match lines.last_mut() {
+            Some(last) if src == ConsoleSrc::Stat && last.src == ConsoleSrc::Stat => {
+                last.text = text
+            }
+            _ => {
+                if lines.len() < MAX_LINES {
+                    lines.push(ConsoleLine { src, text })
+                }
+            }
+        }
+    }
+    /// adds a line of ngspice output as sent to the `SendChar` callback, e.g. `stderr Error: ...`
+    pub fn push_char(&mut self, msg: &str) {
+        match msg.split_once(' ') {
+            Some(("stdout", s)) => self.push(ConsoleSrc::Stdout, s.to_string()),
+            Some(("stderr", s)) => self.push(ConsoleSrc::Stderr, s.to_string()),
+            _ => self.push(ConsoleSrc::Stdout, msg.to_string()),
+        }
+    }
+    /// returns the kept runs, oldest first
+    pub fn runs(&self) -> &[ConsoleRun] {
+        &self.runs
+    }
+    /// returns the run with id, or the latest run if id is None
+    pub fn run(&self, id: Option<usize>) -> Option<&ConsoleRun> {
+        match id {
+            Some(id) => self.runs.iter().find(|r| r.id == id),
+            None => self.runs.last(),
+        }
+    }
+}
+
+impl ConsoleRun {
+    /// returns the lines containing filter (case insensitive)
+    pub fn filtered<'a>(&'a self, filter: &'a str) -> impl Iterator<Item = &'a ConsoleLine> {
+        let filter = filter.to_lowercase();
+        self.lines
+            .iter()
+            .filter(move |l| filter.is_empty() || l.text.to_lowercase().contains(&filter))
+    }
+}
+
+/// where the console is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsoleDock {
+    /// not shown
+    Hidden,
+    /// below the canvas
+    #[default]
+    Bottom,
+    /// right of the canvas
+    Right,
+}
+
+impl ConsoleDock {
+    /// all variants, for use in pick lists
+    pub const ALL: [ConsoleDock; 3] =
+        [ConsoleDock::Hidden, ConsoleDock::Bottom, ConsoleDock::Right];
+}
+
+impl Display for ConsoleDock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ConsoleDock::Hidden => "hidden",
+                ConsoleDock::Bottom => "bottom",
+                ConsoleDock::Right => "right",
+            }
+        )
+    }
+}
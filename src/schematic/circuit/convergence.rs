@@ -0,0 +1,100 @@
+//! convergence assistant
+//! detects failed operating points and transients from the ngspice output, and the retry strategies applied after a failure
+
+use std::fmt::Display;
+
+use crate::schematic::sim_options::SimOptions;
+
+/// returns true if msg, a line of ngspice output, reports a failure to converge
+pub fn is_failure(msg: &str) -> bool {
+    let lower = msg.to_lowercase();
+    [
+        "no convergence",
+        "timestep too small",
+        "singular matrix",
+        "stepping failed",
+        "iteration limit reached",
+        "op failed",
+        "simulation(s) aborted",
+    ]
+    .iter()
+    .any(|kw| lower.contains(kw))
+}
+
+/// retry strategy, applied on top of the strategies before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// skip the direct operating point iteration and step gmin from a large value
+    GminStepping,
+    /// ramp the sources up from zero
+    SourceStepping,
+    /// relax the tolerances and raise the iteration limits
+    Relaxed,
+    /// guess the net voltages from the last converged operating point
+    Nodeset,
+}
+
+impl Strategy {
+    /// all strategies, in order of application
+    pub const ALL: [Strategy; 4] = [
+        Strategy::GminStepping,
+        Strategy::SourceStepping,
+        Strategy::Relaxed,
+        Strategy::Nodeset,
+    ];
+
+    /// applies the strategy to opts. last_op holds the net voltages of the last converged operating point.
+    /// Returns false if the strategy does not apply, leaving opts unchanged
+    pub fn apply(self, opts: &mut SimOptions, last_op: &[(String, f64)]) -> bool {
+        match self {
+            Strategy::GminStepping => {
+                opts.set("noopiter", "");
+                opts.set("gminsteps", "100");
+            }
+            Strategy::SourceStepping => {
+                opts.set("srcsteps", "100");
+            }
+            Strategy::Relaxed => {
+                opts.set("reltol", "0.01");
+                opts.set("abstol", "1e-10");
+                opts.set("vntol", "1e-4");
+                opts.set("itl1", "500");
+                opts.set("itl2", "200");
+                opts.set("itl4", "100");
+            }
+            Strategy::Nodeset => {
+                if last_op.is_empty() {
+                    return false;
+                }
+                opts.set_nodesets(last_op.to_vec());
+            }
+        }
+        true
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Strategy::GminStepping => "gmin stepping",
+                Strategy::SourceStepping => "source stepping",
+                Strategy::Relaxed => "relaxed tolerances",
+                Strategy::Nodeset => "nodeset from last op",
+            }
+        )
+    }
+}
+
+/// outcome of the convergence assistant after a failed analysis
+#[derive(Debug, Clone)]
+pub struct ConvReport {
+    /// the ngspice command which failed
+    pub command: String,
+    /// the strategies tried and whether the analysis converged with them
+    pub attempts: Vec<(Strategy, bool)>,
+    /// the options the analysis converged with, None if every strategy failed
+    pub options: Option<SimOptions>,
+}
@@ -0,0 +1,74 @@
+//! diagnostics
+//! maps ngspice error messages back to the devices and nets they mention
+
+/// schematic element a diagnostic refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagTarget {
+    /// device, by netlist identifier in lower case
+    Device(String),
+    /// net, by net name in lower case
+    Net(String),
+}
+
+/// an ngspice error message attributed to a schematic element
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub target: DiagTarget,
+    /// the original message(s), one per line
+    pub message: String,
+}
+
+/// returns true if msg, a line of ngspice standard output, reports a problem. ngspice reports some errors on standard output
+pub fn is_error(msg: &str) -> bool {
+    let lower = msg.to_lowercase();
+    ["error", "singular", "warning", "unknown", "too small"]
+        .iter()
+        .any(|kw| lower.contains(kw))
+}
+
+/// attributes messages to the devices and nets they mention. devices and nets are netlist identifiers and net names.
+/// A message mentioning a device is attributed to the device only, as ngspice echoes the offending netlist line including its nets.
+pub fn diagnose(messages: &[String], devices: &[String], nets: &[String]) -> Vec<Diagnostic> {
+    let devices: Vec<String> = devices.iter().map(|d| d.to_lowercase()).collect();
+    let nets: Vec<String> = nets
+        .iter()
+        .map(|n| n.to_lowercase())
+        // the ground net is mentioned by many unrelated messages
+        .filter(|n| n != "0")
+        .collect();
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    for msg in messages {
+        let tokens: Vec<String> = msg
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_lowercase())
+            .collect();
+        let mut targets: Vec<DiagTarget> = tokens
+            .iter()
+            .filter(|t| devices.contains(t))
+            .map(|t| DiagTarget::Device(t.clone()))
+            .collect();
+        if targets.is_empty() {
+            targets = tokens
+                .iter()
+                .filter(|t| nets.contains(t))
+                .map(|t| DiagTarget::Net(t.clone()))
+                .collect();
+        }
+        for target in targets {
+            match diagnostics.iter_mut().find(|d| d.target == target) {
+                Some(d) => {
+                    if !d.message.lines().any(|l| l == msg) {
+                        d.message.push('\n');
+                        d.message.push_str(msg);
+                    }
+                }
+                None => diagnostics.push(Diagnostic {
+                    target,
+                    message: msg.clone(),
+                }),
+            }
+        }
+    }
+    diagnostics
+}
@@ -2,8 +2,23 @@
 //! includes paramter editor, toolbar, and the canvas itself
 //! waiting on multiple windows support for new device instance menu
 
+use crate::analysis::trace::{self, Trace, TraceSource};
 use crate::schematic;
+use crate::schematic::atoms::Variation;
+use crate::schematic::circuit::analyses::{
+    device_op_rows, histogram, mc_stats, parse_tolerance, AcSetup, AcSweep, AnalysisKind,
+    AnalysisSetup, DcSetup, DeviceOp, McMode, McSetup, NoiseSetup, OpSort, PzSetup, ResultRow,
+    RunAnalysisKind, SensSetup, StepSetup, StepSweep, TfSetup,
+};
+use crate::schematic::circuit::console::{Console, ConsoleDock, ConsoleSrc};
+use crate::schematic::circuit::convergence::{self, ConvReport, Strategy};
+use crate::schematic::circuit::diagnostics;
+use crate::schematic::circuit::op_table::{OpTable, OpTableSort};
 use crate::schematic::circuit::{Circuit, CircuitAtom, Msg};
+use crate::schematic::design_params::DesignParam;
+use crate::schematic::eng;
+use crate::schematic::eseries::ESeries;
+use crate::schematic::sim_options::{Method, OVERRIDE_ANALYSES};
 use crate::schematic::viewport::CompositeMsg;
 use crate::schematic::viewport::VCTransformLockedAspect;
 use crate::transforms::VSPoint;
@@ -14,8 +29,13 @@ use crate::schematic::Schematic;
 use crate::IcedStruct;
 use iced::keyboard::Modifiers;
 use iced::widget::canvas::Event;
-use iced::widget::{row, text, text_input};
+use iced::widget::{
+    button, checkbox, pick_list, row, scrollable, slider, text, text_input, Column,
+};
 use iced::{Element, Length};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use colored::Colorize;
@@ -25,7 +45,11 @@ use paprika::*;
 #[derive(Debug, Default)]
 struct SpManager {
     vecvals: Mutex<Vec<PkVecvaluesall>>,
+    /// true while ngspice runs a command in its background thread
+    bg_running: AtomicBool,
     vecinfo: Option<PkVecinfoall>,
+    /// ngspice output and commands, for display in the console
+    console: Mutex<Console>,
 }
 
 impl SpManager {
@@ -48,19 +72,23 @@ impl paprika::PkSpiceManager for SpManager {
             _ => msg.magenta().strikethrough(),
         };
         println!("{}", msgc);
+        self.console.lock().unwrap().push_char(&msg);
     }
     fn cb_send_stat(&mut self, msg: String, id: i32) {
         println!("{}", msg.blue());
+        self.console.lock().unwrap().push(ConsoleSrc::Stat, msg);
     }
     fn cb_ctrldexit(&mut self, status: i32, is_immediate: bool, is_quit: bool, id: i32) {}
     fn cb_send_init(&mut self, pkvecinfoall: PkVecinfoall, id: i32) {
         self.vecinfo = Some(pkvecinfoall);
     }
     fn cb_send_data(&mut self, pkvecvaluesall: PkVecvaluesall, count: i32, id: i32) {
-        // this is called every simulation step when running tran
-        self.vecvals.try_lock().unwrap().push(pkvecvaluesall);
+        // this is called every simulation step when running tran, from the background thread during live runs
+        self.vecvals.lock().unwrap().push(pkvecvaluesall);
+    }
+    fn cb_bgt_state(&mut self, is_fin: bool, id: i32) {
+        self.bg_running.store(!is_fin, Ordering::SeqCst);
     }
-    fn cb_bgt_state(&mut self, is_fin: bool, id: i32) {}
 }
 
 #[derive(Debug, Clone)]
@@ -68,7 +96,72 @@ pub enum CircuitPageMsg {
     ViewportEvt(viewport::CompositeMsg<schematic::Msg<Msg, CircuitAtom>>),
     ParamChanged(String),
     ParamSubmit,
-    HzChanged(String),
+    AcSweepSelected(AcSweep),
+    AcPointsChanged(String),
+    AcFstartChanged(String),
+    AcFstopChanged(String),
+    DcSrc1Changed(String),
+    DcStart1Changed(String),
+    DcStop1Changed(String),
+    DcStep1Changed(String),
+    DcSrc2Changed(String),
+    DcStart2Changed(String),
+    DcStop2Changed(String),
+    DcStep2Changed(String),
+    AnalysisSelected(AnalysisKind),
+    SetupChanged(usize, String),
+    RunAnalysis,
+    LocateDevice(String),
+    TolChanged(String),
+    TolSubmit,
+    McModeSelected(McMode),
+    McAnalysisSelected(RunAnalysisKind),
+    McRunsChanged(String),
+    McMeasChanged(String),
+    McModelsChanged(String),
+    RunMonteCarlo,
+    McStop,
+    StepTargetChanged(String),
+    StepSweepSelected(StepSweep),
+    StepValuesChanged(String),
+    StepAnalysisSelected(RunAnalysisKind),
+    RunStep,
+    DesignParamNameChanged(usize, String),
+    DesignParamExprChanged(usize, String),
+    DesignParamAdd,
+    KeepConvOptions,
+    TranUicToggled(bool),
+    TranLive,
+    TranStop,
+    StreamTick,
+    TranScrubbed(f32),
+    HeatmapToggled(bool),
+    SimOptionChanged(&'static str, String),
+    SimMethodSelected(Method),
+    SimTempChanged(String),
+    SimClearNodesets,
+    OverrideAnalysisSelected(&'static str),
+    OverrideChanged(String),
+    TempSweepChanged(String),
+    TempSweepAnalysisSelected(RunAnalysisKind),
+    RunTempSweep,
+    DismissConv,
+    OpSortSelected(OpSort),
+    OpTableToggled(bool),
+    OpTableFilterChanged(String),
+    OpTableSortSelected(OpTableSort),
+    OpTableExport,
+    LocateNet(String),
+    ConsoleDockSelected(ConsoleDock),
+    ConsoleRunSelected(usize),
+    ConsoleFilterChanged(String),
+    ConsoleCopy,
+    ConsoleInputChanged(String),
+    ConsoleSubmit,
+    ESeriesSelected(ESeries),
+    StdValuePicked(f64),
+    SnapPassives,
+    DesignParamRemove(usize),
     StepChanged(String),
     TranChanged(String),
 }
@@ -86,14 +179,88 @@ pub struct CircuitSchematicPage {
     /// ngspice library
     lib: PkSpice<SpManager>,
     /// traces from certain simulations e.g. transient
-    pub traces: Option<Vec<Vec<VSPoint>>>,
+    pub traces: Option<Vec<Trace>>,
+    /// traces of a transient analysis still running, to be shown without leaving the schematic
+    pub live_traces: Option<Vec<Trace>>,
+    /// histogram of the measurement of the last monte carlo or corner analysis
+    pub histogram: Option<Vec<Trace>>,
+    /// text to be copied to the clipboard
+    pub clipboard: Option<String>,
+    /// legend of the traces, naming consecutive groups of traces. Each entry is a name and the number of traces in its group
+    pub legend: Vec<(String, usize)>,
 
     /// active device - some if only 1 device selected, otherwise is none
     active_element: Option<CircuitAtom>,
     /// parameter editor text
     param: String,
-    /// ac simulation frequency (hertz)
-    ac_hz: String,
+    /// error of the parameter editor text, validated as you type
+    param_error: Option<String>,
+    /// tolerance editor text (percent)
+    tol: String,
+    /// error of the tolerance editor text, validated as you type
+    tol_error: Option<String>,
+    /// standard value series offered when editing resistors and capacitors
+    eseries: ESeries,
+    /// ac simulation setup
+    ac: AcSetup,
+    /// dc sweep simulation setup
+    dc: DcSetup,
+    /// analysis shown in the analysis setup bar
+    analysis: AnalysisKind,
+    /// noise simulation setup
+    noise: NoiseSetup,
+    /// pole-zero simulation setup
+    pz: PzSetup,
+    /// transfer function simulation setup
+    tf: TfSetup,
+    /// sensitivity simulation setup
+    sens: SensSetup,
+    /// monte carlo and corner analysis setup
+    mc: McSetup,
+    /// parameter stepping setup
+    step: StepSetup,
+    /// tabulated results of the last noise/pz/tf/sens or monte carlo analysis
+    results: Vec<ResultRow>,
+    /// where the console is shown
+    console_dock: ConsoleDock,
+    /// id of the console run shown, the latest run if None
+    console_run: Option<usize>,
+    /// console search text, only lines containing it are shown
+    console_filter: String,
+    /// ngspice command entered in the console
+    console_input: String,
+    /// analysis whose option overrides are shown in the sim options bar
+    override_analysis: &'static str,
+    /// analysis repeated at every temperature of the temperature sweep
+    temp_sweep_analysis: RunAnalysisKind,
+    /// outcome of the convergence assistant after the last failed op or tran
+    convergence: Option<ConvReport>,
+    /// net voltages of the last converged operating point, used as nodeset hints
+    last_op: Vec<(String, f64)>,
+    /// device currents and powers of the last converged operating point
+    device_op: Vec<DeviceOp>,
+    /// order of the device currents and powers in the result table
+    op_sort: OpSort,
+    /// node voltages and branch currents of the last converged operating point
+    op_table: OpTable,
+    /// show the operating point table beside the canvas
+    show_op_table: bool,
+    /// id and length of the console run the diagnostics were made from
+    diag_key: (usize, usize),
+    /// use the initial conditions rather than solving the operating point at the start of transient analyses
+    tran_uic: bool,
+    /// steps of the last converged transient analysis, for the time scrubber
+    tran_steps: Vec<PkVecvaluesall>,
+    /// lowest and highest net voltage over tran_steps, ground included, for the heatmap
+    tran_range: Option<(f32, f32)>,
+    /// true while a live transient analysis runs in the background
+    streaming: bool,
+    /// the monte carlo or corner analysis running in the background, if any
+    mc_run: Option<McRun>,
+    /// time shown by the time scrubber
+    scrub_time: f32,
+    /// colour nets by voltage
+    heatmap: bool,
     /// tran simulation step size (seconds)
     tran_step: String,
     /// tran simulation end time (seconds)
@@ -148,21 +315,1031 @@ impl Default for CircuitSchematicPage {
             net_name: Default::default(),
             active_element: Default::default(),
             param: Default::default(),
+            param_error: None,
+            tol: Default::default(),
+            tol_error: None,
+            eseries: ESeries::default(),
             spmanager,
             lib,
             traces: None,
-            ac_hz: String::from("60"),
+            live_traces: None,
+            histogram: None,
+            clipboard: None,
+            legend: vec![],
+            ac: AcSetup::default(),
+            dc: DcSetup::default(),
+            analysis: AnalysisKind::default(),
+            noise: NoiseSetup::default(),
+            pz: PzSetup::default(),
+            tf: TfSetup::default(),
+            sens: SensSetup::default(),
+            mc: McSetup::default(),
+            step: StepSetup::default(),
+            results: vec![],
+            console_dock: ConsoleDock::default(),
+            console_run: None,
+            console_filter: String::new(),
+            console_input: String::new(),
+            diag_key: (0, 0),
+            override_analysis: "tran",
+            temp_sweep_analysis: RunAnalysisKind::default(),
+            convergence: None,
+            last_op: vec![],
+            device_op: vec![],
+            op_sort: OpSort::default(),
+            op_table: OpTable::default(),
+            show_op_table: false,
+            tran_uic: false,
+            tran_steps: vec![],
+            tran_range: None,
+            streaming: false,
+            mc_run: None,
+            scrub_time: 0.0,
+            heatmap: false,
             tran_step: String::from("10u"),
             tran_end: String::from("1m"),
         }
     }
 }
 
+/// a monte carlo or corner analysis running in the background, one variation at a time
+struct McRun {
+    /// variations not run yet, the next one last
+    pending: Vec<Variation>,
+    /// number of variations in all
+    total: usize,
+    /// control line of the analysis of every run
+    cmd: String,
+    /// traces of the finished runs
+    traces: Vec<Trace>,
+    /// measured results of the finished runs
+    values: Vec<f64>,
+    /// warnings listed above the measured results
+    warnings: Vec<ResultRow>,
+}
+
+/// maximum number of varied values enumerated in a corner analysis - limits the number of runs to 2^10
+const MAX_CORNER_VALUES: usize = 10;
+
+/// returns true if msg writes the netlist or sends commands to ngspice, which is refused while an analysis runs in the background
+fn starts_run(msg: &CircuitPageMsg) -> bool {
+    use iced::keyboard::KeyCode;
+    match msg {
+        CircuitPageMsg::RunAnalysis
+        | CircuitPageMsg::RunMonteCarlo
+        | CircuitPageMsg::RunStep
+        | CircuitPageMsg::RunTempSweep
+        | CircuitPageMsg::TranLive
+        | CircuitPageMsg::ConsoleSubmit => true,
+        // op, ac, tran and dc keys
+        CircuitPageMsg::ViewportEvt(msgs) => matches!(
+            &msgs.content_msg,
+            schematic::Msg::Event(
+                Event::Keyboard(iced::keyboard::Event::KeyPressed { key_code, modifiers }),
+                _,
+            ) if matches!(
+                (key_code, *modifiers),
+                (KeyCode::Space, Modifiers::CTRL) | (KeyCode::T | KeyCode::D, Modifiers::SHIFT)
+            ) || (*key_code == KeyCode::Space && modifiers.is_empty())
+        ),
+        _ => false,
+    }
+}
+
+impl CircuitSchematicPage {
+    /// sends cmd to ngspice, logging it in the console. Returns false if ngspice reported an error
+    fn command(&self, cmd: &str) -> bool {
+        self.spmanager
+            .console
+            .lock()
+            .unwrap()
+            .push(ConsoleSrc::Command, cmd.to_string());
+        // ngspice returns 0 on success
+        !self.lib.command(cmd)
+    }
+    /// sends cmd to ngspice to be run in its background thread, logging it in the console. Returns false if ngspice reported an error
+    fn bg_command(&self, cmd: &str) -> bool {
+        self.spmanager
+            .console
+            .lock()
+            .unwrap()
+            .push(ConsoleSrc::Command, format!("bg_{}", cmd));
+        !self.lib.command_bg(cmd)
+    }
+    /// sends the analysis command cmd to ngspice, preceded by the option overrides of the analysis.
+    /// Returns false if ngspice reported an error
+    fn analysis_command(&self, cmd: &str) -> bool {
+        if let Some(options) = self
+            .viewport
+            .content
+            .content
+            .sim_options()
+            .override_command(cmd)
+        {
+            self.command(&options);
+        }
+        let save = self.save_command(cmd);
+        if let Some(save) = &save {
+            self.command(save);
+        }
+        let ok = self.command(cmd);
+        if save.is_some() {
+            // later analyses of the same circuit, e.g. steps, start from the default
+            self.command("delete all");
+        }
+        ok
+    }
+    /// sends the analysis command cmd to ngspice to be run in the background, preceded by the option overrides of the analysis.
+    /// Returns false if ngspice reported an error. Once the run ends, `bg_analysis_finished` should be called
+    fn bg_analysis_command(&self, cmd: &str) -> bool {
+        self.spmanager.vecvals.lock().unwrap().clear();
+        // set before the background thread reports in, so the first tick does not end the run
+        self.spmanager.bg_running.store(true, Ordering::SeqCst);
+        if let Some(options) = self
+            .viewport
+            .content
+            .content
+            .sim_options()
+            .override_command(cmd)
+        {
+            self.command(&options);
+        }
+        if let Some(save) = self.save_command(cmd) {
+            self.command(&save);
+        }
+        let ok = self.bg_command(cmd);
+        if !ok {
+            self.spmanager.bg_running.store(false, Ordering::SeqCst);
+            self.bg_analysis_finished(cmd);
+        }
+        ok
+    }
+    /// restores the vectors saved by default after the background analysis command cmd ended
+    fn bg_analysis_finished(&self, cmd: &str) {
+        if self.save_command(cmd).is_some() {
+            self.command("delete all");
+        }
+    }
+    /// returns the command setting the vectors saved by the analysis command cmd, if they differ from the default of every node voltage and branch current.
+    /// Transient analyses are restricted to the probed signals, operating points save the device currents for the annotations as well
+    fn save_command(&self, cmd: &str) -> Option<String> {
+        match cmd.split_whitespace().next() {
+            Some("tran") => self.viewport.content.content.probe_save_command(),
+            Some("op") => self.viewport.content.content.op_save_command(),
+            _ => None,
+        }
+    }
+    /// starts a new console run and sources the netlist
+    fn source_netlist(&self) {
+        self.spmanager.console.lock().unwrap().begin_run();
+        self.command("source netlist.cir");
+    }
+    /// maps the errors reported by ngspice in the latest run to the devices and nets they mention, if the run has new output
+    fn refresh_diagnostics(&mut self) {
+        let console = self.spmanager.console.lock().unwrap();
+        let Some(run) = console.run(None) else {
+            return;
+        };
+        let key = (run.id, run.lines.len());
+        if key == self.diag_key {
+            return;
+        }
+        self.diag_key = key;
+        let errors: Vec<String> = run
+            .lines
+            .iter()
+            .filter(|l| match l.src {
+                ConsoleSrc::Stderr => true,
+                ConsoleSrc::Stdout => diagnostics::is_error(&l.text),
+                ConsoleSrc::Stat | ConsoleSrc::Command => false,
+            })
+            .map(|l| l.text.clone())
+            .collect();
+        drop(console);
+        self.viewport.content.content.diagnose(&errors);
+        self.viewport.passive_cache.clear();
+    }
+    /// returns true if the output of the latest console run reports a failure to converge
+    fn run_failed(&self) -> bool {
+        let console = self.spmanager.console.lock().unwrap();
+        console.run(None).is_some_and(|r| {
+            r.lines
+                .iter()
+                .any(|l| l.src != ConsoleSrc::Command && convergence::is_failure(&l.text))
+        })
+    }
+    /// returns the first line of output of the latest console run reporting an error or a failure to converge
+    fn run_error(&self) -> Option<String> {
+        let console = self.spmanager.console.lock().unwrap();
+        console.run(None).and_then(|r| {
+            r.lines
+                .iter()
+                .find(|l| {
+                    l.src != ConsoleSrc::Command
+                        && (convergence::is_failure(&l.text)
+                            || l.text.to_lowercase().contains("error"))
+                })
+                .map(|l| l.text.clone())
+        })
+    }
+    /// runs cmd on the sourced circuit and returns true if it converged: ngspice accepted the command
+    /// (`analysis_command` returns true on success) and reported no failure to converge
+    fn converged(&self, cmd: &str) -> bool {
+        self.spmanager.vecvals.lock().unwrap().clear();
+        self.analysis_command(cmd) && !self.run_failed()
+    }
+    /// runs cmd on the sourced circuit. If it fails to converge, retries with increasingly forgiving options
+    /// and records the outcome for display. Returns false if every attempt failed
+    fn run_converging(&mut self, cmd: &str) -> bool {
+        if self.converged(cmd) {
+            self.convergence = None;
+            return true;
+        }
+        let base = self.viewport.content.content.sim_options().clone();
+        let mut opts = base.clone();
+        let mut report = ConvReport {
+            command: cmd.to_string(),
+            attempts: vec![],
+            options: None,
+        };
+        for strategy in Strategy::ALL {
+            if !strategy.apply(&mut opts, &self.last_op) {
+                continue;
+            }
+            *self.viewport.content.content.sim_options_mut() = opts.clone();
+            self.viewport.update(CompositeMsg {
+                content_msg: schematic::Msg::ContentMsg(Msg::NetList(Variation::Nominal)),
+                viewport_msg: viewport::Msg::None,
+            });
+            self.source_netlist();
+            let converged = self.converged(cmd);
+            report.attempts.push((strategy, converged));
+            if converged {
+                report.options = Some(opts);
+                break;
+            }
+        }
+        // the options are only kept at the user's request
+        *self.viewport.content.content.sim_options_mut() = base;
+        let converged = report.options.is_some();
+        self.results = report
+            .attempts
+            .iter()
+            .map(|(s, ok)| ResultRow {
+                name: s.to_string(),
+                value: String::from(if *ok { "converged" } else { "failed" }),
+                device: None,
+            })
+            .collect();
+        self.convergence = Some(report);
+        converged
+    }
+    /// returns the console panel
+    fn console_view(&self) -> Element<CircuitPageMsg> {
+        let console = self.spmanager.console.lock().unwrap();
+        let run = console.run(self.console_run);
+        let ids: Vec<usize> = console.runs().iter().map(|r| r.id).collect();
+        let lines: Vec<Element<CircuitPageMsg>> = run
+            .map(|r| {
+                r.filtered(&self.console_filter)
+                    .map(|l| {
+                        let (prefix, color) = match l.src {
+                            ConsoleSrc::Stdout => ("", iced::Color::from_rgb(0.5, 1.0, 0.5)),
+                            ConsoleSrc::Stderr => ("", iced::Color::from_rgb(1.0, 0.4, 0.4)),
+                            ConsoleSrc::Stat => ("", iced::Color::from_rgb(0.5, 0.7, 1.0)),
+                            ConsoleSrc::Command => ("> ", iced::Color::from_rgb(1.0, 1.0, 1.0)),
+                        };
+                        text(format!("{}{}", prefix, l.text))
+                            .size(14)
+                            .style(color)
+                            .into()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let header = row![
+            text("console "),
+            pick_list(ids, run.map(|r| r.id), CircuitPageMsg::ConsoleRunSelected).width(70),
+            text_input("search", &self.console_filter)
+                .width(Length::Fill)
+                .on_input(CircuitPageMsg::ConsoleFilterChanged),
+            button("copy").on_press(CircuitPageMsg::ConsoleCopy),
+        ]
+        .spacing(2);
+        let input = text_input("ngspice command", &self.console_input)
+            .on_input(CircuitPageMsg::ConsoleInputChanged)
+            .on_submit(CircuitPageMsg::ConsoleSubmit);
+        let panel = iced::widget::column![
+            header,
+            scrollable(Column::with_children(lines).spacing(1))
+                .height(Length::Fill)
+                .width(Length::Fill),
+            input
+        ];
+        match self.console_dock {
+            ConsoleDock::Right => panel.width(300).into(),
+            _ => panel.height(200).into(),
+        }
+    }
+    /// returns the operating point table panel
+    fn op_table_view(&self) -> Element<CircuitPageMsg> {
+        let rows: Vec<Element<CircuitPageMsg>> = self
+            .op_table
+            .visible()
+            .into_iter()
+            .map(|r| {
+                let label = text(format!("{}: {}", r.name, r.formatted())).size(14);
+                // jump to the device or net on the schematic
+                let msg = match (r.device(), r.net()) {
+                    (Some(id), _) => Some(CircuitPageMsg::LocateDevice(id.to_string())),
+                    (_, Some(net)) => Some(CircuitPageMsg::LocateNet(net.to_string())),
+                    _ => None,
+                };
+                match msg {
+                    Some(msg) => button(label).on_press(msg).into(),
+                    None => label.into(),
+                }
+            })
+            .collect();
+        let header = row![
+            text_input("filter", &self.op_table.filter)
+                .width(Length::Fill)
+                .on_input(CircuitPageMsg::OpTableFilterChanged),
+            pick_list(
+                &OpTableSort::ALL[..],
+                Some(self.op_table.sort),
+                CircuitPageMsg::OpTableSortSelected
+            )
+            .width(100),
+            button("csv").on_press(CircuitPageMsg::OpTableExport),
+        ]
+        .spacing(2);
+        iced::widget::column![
+            header,
+            scrollable(Column::with_children(rows).spacing(2)).height(Length::Fill)
+        ]
+        .width(300)
+        .into()
+    }
+    /// returns a mut reference to the design parameter table
+    fn design_params_mut(&mut self) -> &mut Vec<DesignParam> {
+        self.viewport
+            .content
+            .content
+            .design_params_mut()
+            .params_mut()
+    }
+    /// re-evaluates device value expressions after the design parameters changed
+    fn design_params_changed(&mut self) {
+        self.viewport.content.content.evaluate_params();
+        self.viewport.passive_cache.clear();
+    }
+    /// returns the ngspice control line of analysis, as set up in the toolbars
+    fn run_command(&self, analysis: RunAnalysisKind) -> String {
+        match analysis {
+            RunAnalysisKind::Tran => format!(
+                "tran {} {}{}",
+                self.tran_step,
+                self.tran_end,
+                if self.tran_uic { " uic" } else { "" }
+            ),
+            RunAnalysisKind::Ac => self.ac.command(),
+            RunAnalysisKind::Dc => self.dc.command(),
+        }
+    }
+    /// writes and sources the netlist with values varied according to variation, then runs analysis and returns its traces
+    fn run_traces(&mut self, analysis: RunAnalysisKind, variation: Variation) -> Vec<Trace> {
+        self.viewport.update(CompositeMsg {
+            content_msg: schematic::Msg::ContentMsg(Msg::NetList(variation)),
+            viewport_msg: viewport::Msg::None,
+        });
+        self.source_netlist(); // results pointer array starts at same address
+        self.simulate(analysis)
+    }
+    /// runs analysis on the sourced circuit and returns its traces
+    fn simulate(&mut self, analysis: RunAnalysisKind) -> Vec<Trace> {
+        self.spmanager.vecvals.lock().unwrap().clear();
+        self.analysis_command(&self.run_command(analysis)); // ngspice recommends sending in control statements separately, not as part of netlist
+        match analysis {
+            RunAnalysisKind::Tran => self.tran_traces(),
+            RunAnalysisKind::Ac => self.ac_traces(),
+            RunAnalysisKind::Dc => self.dc_traces(),
+        }
+    }
+    /// runs the monte carlo or corner analysis, overlaying the traces of every run
+    /// and tabulating the histogram of the measured results
+    fn run_monte_carlo(&mut self) {
+        if let Err(e) = self
+            .viewport
+            .content
+            .content
+            .models_mut()
+            .set_variations(&self.mc.models)
+        {
+            self.results = vec![ResultRow {
+                name: String::from("error"),
+                value: e,
+                device: None,
+            }];
+            return;
+        }
+        let mut warnings = vec![];
+        let variations: Vec<Variation> = match self.mc.mode {
+            McMode::Random => match self.mc.run_count() {
+                Ok(runs) => vec![Variation::Random; runs],
+                Err(e) => {
+                    self.results = vec![ResultRow {
+                        name: String::from("error"),
+                        value: e,
+                        device: None,
+                    }];
+                    return;
+                }
+            },
+            McMode::Corners => {
+                let count = self.viewport.content.content.variations_count();
+                if count > MAX_CORNER_VALUES {
+                    warnings.push(ResultRow {
+                        name: String::from("warning"),
+                        value: format!(
+                            "only the first {} of {} varied values are taken to their corners",
+                            MAX_CORNER_VALUES, count
+                        ),
+                        device: None,
+                    });
+                }
+                let k = count.min(MAX_CORNER_VALUES);
+                (0..1u64 << k).map(Variation::Corner).collect()
+            }
+        };
+        let total = variations.len();
+        self.mc_run = Some(McRun {
+            pending: variations.into_iter().rev().collect(),
+            total,
+            cmd: self.run_command(self.mc.analysis),
+            traces: vec![],
+            values: vec![],
+            warnings,
+        });
+        self.next_mc_run();
+    }
+    /// starts the next run of the monte carlo analysis in the background, or shows the results once every run ended
+    fn next_mc_run(&mut self) {
+        let Some(mc_run) = &mut self.mc_run else {
+            return;
+        };
+        let Some(variation) = mc_run.pending.pop() else {
+            let mc_run = self.mc_run.take().unwrap();
+            let name = self.mc.meas_name().unwrap_or_default();
+            let mut results = mc_run.warnings;
+            results.extend(mc_stats(name, &mc_run.values));
+            self.results = results;
+            self.traces = Some(mc_run.traces);
+            self.histogram = histogram(name, &mc_run.values).map(|h| vec![h]);
+            return;
+        };
+        let run = mc_run.total - mc_run.pending.len();
+        let cmd = mc_run.cmd.clone();
+        self.results = vec![ResultRow {
+            name: String::from("monte carlo"),
+            value: format!("run {} of {}", run, mc_run.total),
+            device: None,
+        }];
+        self.viewport.update(CompositeMsg {
+            content_msg: schematic::Msg::ContentMsg(Msg::NetList(variation)),
+            viewport_msg: viewport::Msg::None,
+        });
+        self.source_netlist(); // results pointer array starts at same address
+        if !self.bg_analysis_command(&cmd) {
+            self.mc_run = None;
+            self.results = vec![ResultRow {
+                name: String::from("monte carlo failed"),
+                value: format!("run {}: see the console", run),
+                device: None,
+            }];
+        }
+    }
+    /// collects the traces and measurement of the monte carlo run just ended, then starts the next run
+    fn mc_run_finished(&mut self) {
+        let Some(cmd) = self.mc_run.as_ref().map(|r| r.cmd.clone()) else {
+            return;
+        };
+        self.bg_analysis_finished(&cmd);
+        let traces = match self.mc.analysis {
+            RunAnalysisKind::Tran => self.tran_traces(),
+            RunAnalysisKind::Ac => self.ac_traces(),
+            RunAnalysisKind::Dc => self.dc_traces(),
+        };
+        let mut value = None;
+        if let (Some(meas), Some(name)) = (self.mc.meas_command(), self.mc.meas_name()) {
+            self.command(&meas);
+            value = self.vec_value(&self.lib.get_cur_plot(), name).map(|v| v.re);
+        }
+        if let Some(mc_run) = &mut self.mc_run {
+            mc_run.traces.extend(traces);
+            mc_run.values.extend(value);
+        }
+        self.next_mc_run();
+    }
+    /// runs the analysis once for every step value, overlaying the traces of every step with a legend naming the step value
+    fn run_step(&mut self) {
+        let values = match self.step.values() {
+            Ok(values) => values,
+            Err(e) => {
+                self.results = vec![ResultRow {
+                    name: String::from("error"),
+                    value: e,
+                    device: None,
+                }];
+                return;
+            }
+        };
+        let target = self.step.target.trim().to_string();
+        // devices and device parameters (@dev[param]) are altered in place, anything else is taken to be a .param
+        let is_device = target.starts_with('@')
+            || self
+                .viewport
+                .content
+                .content
+                .device_by_id(&target)
+                .is_some();
+        self.viewport.update(CompositeMsg {
+            content_msg: schematic::Msg::ContentMsg(Msg::NetList(Variation::Nominal)),
+            viewport_msg: viewport::Msg::None,
+        });
+        self.source_netlist(); // results pointer array starts at same address
+        let mut traces = vec![];
+        self.legend.clear();
+        for value in values {
+            if is_device {
+                self.command(&format!("alter {} = {}", target, value));
+            } else {
+                self.command(&format!("alterparam {} = {}", target, value));
+                self.command("reset"); // alterparam takes effect once the circuit is reloaded
+            }
+            let step_traces = self.simulate(self.step.analysis);
+            self.legend
+                .push((format!("{} = {}", target, value), step_traces.len()));
+            traces.extend(step_traces);
+        }
+        self.traces = Some(traces);
+    }
+    /// runs the temperature sweep analysis at every temperature of the sweep, overlaying the traces
+    fn run_temp_sweep(&mut self) {
+        let temps = match self.viewport.content.content.sim_options().temps() {
+            Ok(temps) => temps,
+            Err(e) => {
+                self.results = vec![ResultRow {
+                    name: String::from("error"),
+                    value: e,
+                    device: None,
+                }];
+                return;
+            }
+        };
+        let temp = self.viewport.content.content.sim_options().temp.clone();
+        let mut traces = vec![];
+        self.legend.clear();
+        for t in temps {
+            // the circuit temperature is written to the netlist as .temp
+            self.viewport.content.content.sim_options_mut().temp = t.to_string();
+            let temp_traces = self.run_traces(self.temp_sweep_analysis, Variation::Nominal);
+            self.legend.push((format!("{} degC", t), temp_traces.len()));
+            traces.extend(temp_traces);
+        }
+        self.viewport.content.content.sim_options_mut().temp = temp;
+        self.traces = Some(traces);
+    }
+    /// returns traces from the data sent by ngspice during the most recent transient analysis, over time.
+    /// If probes are placed, returns the probed signals in order of the probe signals
+    fn tran_traces(&self) -> Vec<Trace> {
+        // live runs push steps from the background thread
+        let pk_results = self.spmanager.vecvals.lock().unwrap();
+
+        let Some(first) = pk_results.first() else {
+            return vec![];
+        };
+        let signals = self.viewport.content.content.probe_signals();
+        if !signals.is_empty() {
+            let index = |name: &str| {
+                first
+                    .vecsa
+                    .iter()
+                    .position(|v| v.name.eq_ignore_ascii_case(name))
+            };
+            let Some(x_i) = index("time") else {
+                return vec![];
+            };
+            return signals
+                .iter()
+                .map(|s| {
+                    // a signal ngspice did not send is left empty, keeping traces in order of the signals
+                    let points = match index(&s.vec) {
+                        Some(vec_i) => {
+                            let ref_i = s.ref_vec.as_deref().and_then(index);
+                            pk_results
+                                .iter()
+                                .map(|step_val| {
+                                    let ref_val = ref_i.map_or(0.0, |i| step_val.vecsa[i].creal);
+                                    VSPoint::new(
+                                        step_val.vecsa[x_i].creal as f32,
+                                        (s.sign * (step_val.vecsa[vec_i].creal - ref_val)) as f32,
+                                    )
+                                })
+                                .collect()
+                        }
+                        None => vec![],
+                    };
+                    Trace::new(
+                        &s.name,
+                        trace::unit_of(&s.name),
+                        "s",
+                        TraceSource::Tran,
+                        points,
+                    )
+                })
+                .collect();
+        }
+        let trace_count = first.count as usize;
+        let mut results: Vec<Vec<VSPoint>> = Vec::with_capacity(trace_count);
+        for _ in 0..trace_count {
+            results.push(Vec::with_capacity(pk_results.len()));
+        }
+
+        let Some(x_i) = first.vecsa.iter().position(|x| x.name == "time") else {
+            return vec![];
+        };
+        for step_val in pk_results.iter() {
+            for (trace_i, trace_val) in step_val.vecsa.iter().enumerate() {
+                results[trace_i].push(VSPoint::new(
+                    step_val.vecsa[x_i].creal as f32,
+                    trace_val.creal as f32,
+                ));
+            }
+        }
+        results.remove(x_i);
+        first
+            .vecsa
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != x_i)
+            .zip(results)
+            .map(|((_, v), points)| {
+                Trace::new(
+                    &v.name,
+                    trace::unit_of(&v.name),
+                    "s",
+                    TraceSource::Tran,
+                    points,
+                )
+            })
+            .collect()
+    }
+    /// returns true while a live transient analysis or a monte carlo analysis runs in the background
+    pub fn is_streaming(&self) -> bool {
+        self.streaming || self.mc_run.is_some()
+    }
+    /// stores the steps of the transient analysis just ended for the time scrubber, with the range of their net voltages
+    fn set_tran_steps(&mut self) {
+        self.tran_steps = self.spmanager.vecvals.lock().unwrap().clone();
+        let nets: HashSet<String> = self
+            .viewport
+            .content
+            .content
+            .net_names()
+            .into_iter()
+            .collect();
+        self.tran_range = self
+            .tran_steps
+            .iter()
+            .flat_map(|step| step.vecsa.iter())
+            .filter(|v| nets.contains(&v.name.to_lowercase()))
+            .map(|v| v.creal as f32)
+            .fold(None, |range, v| match range {
+                // ground is at 0V
+                None => Some((v.min(0.0), v.max(0.0))),
+                Some((min, max)) => Some((v.min(min), v.max(max))),
+            });
+    }
+    /// returns the start and end time of the stored transient steps
+    fn tran_span(&self) -> Option<(f32, f32)> {
+        let first = self.tran_steps.first()?;
+        let x_i = first.vecsa.iter().position(|v| v.name == "time")?;
+        let last = self.tran_steps.last()?;
+        Some((first.vecsa[x_i].creal as f32, last.vecsa[x_i].creal as f32))
+    }
+    /// returns the values of the stored transient steps at time t, linearly interpolated between steps
+    fn tran_at(&self, t: f32) -> Option<PkVecvaluesall> {
+        let first = self.tran_steps.first()?;
+        let x_i = first.vecsa.iter().position(|v| v.name == "time")?;
+        let time = |s: &PkVecvaluesall| s.vecsa[x_i].creal;
+        let t = t as f64;
+        // last step at or before t
+        let k = self
+            .tran_steps
+            .partition_point(|s| time(s) <= t)
+            .saturating_sub(1);
+        let mut pkvecvaluesall = self.tran_steps[k].clone();
+        if let Some(next) = self.tran_steps.get(k + 1) {
+            let dt = time(next) - time(&pkvecvaluesall);
+            let f = if dt > 0.0 {
+                ((t - time(&pkvecvaluesall)) / dt).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            for (v, v1) in pkvecvaluesall.vecsa.iter_mut().zip(&next.vecsa) {
+                v.creal += (v1.creal - v.creal) * f;
+            }
+        }
+        Some(pkvecvaluesall)
+    }
+    /// returns the setup of the analysis shown in the analysis setup bar
+    fn setup(&self) -> &dyn AnalysisSetup {
+        match self.analysis {
+            AnalysisKind::Noise => &self.noise,
+            AnalysisKind::Pz => &self.pz,
+            AnalysisKind::Tf => &self.tf,
+            AnalysisKind::Sens => &self.sens,
+        }
+    }
+    /// returns a mut reference to the setup of the analysis shown in the analysis setup bar
+    fn setup_mut(&mut self) -> &mut dyn AnalysisSetup {
+        match self.analysis {
+            AnalysisKind::Noise => &mut self.noise,
+            AnalysisKind::Pz => &mut self.pz,
+            AnalysisKind::Tf => &mut self.tf,
+            AnalysisKind::Sens => &mut self.sens,
+        }
+    }
+    /// returns the first value of vector name in plot, if it exists
+    fn vec_value(&self, plot: &str, name: &str) -> Option<num::Complex<f64>> {
+        self.lib
+            .get_vec_info(&format!("{}.{}", plot, name))
+            .and_then(|v| v.data_complex().first().copied())
+    }
+    /// returns noise spectral density traces (dB re 1V/sqrt(Hz) or 1A/sqrt(Hz), over frequency)
+    /// and the integrated noise totals from the most recent noise analysis
+    fn noise_results(&self) -> (Vec<Trace>, Vec<ResultRow>) {
+        let mut traces = vec![];
+        let mut rows = vec![];
+        // ngspice lists the most recent plots first. Noise analysis creates a spectrum plot and an integrated noise plot
+        for plot in self.lib.get_all_plots() {
+            let vecs = self.lib.get_all_vecs(&plot);
+            if traces.is_empty() && vecs.iter().any(|n| n == "onoise_spectrum") {
+                if let Some(freq) = self.lib.get_vec_info(&format!("{}.frequency", plot)) {
+                    let x: Vec<f32> = freq.data_real().iter().map(|f| *f as f32).collect();
+                    for name in vecs.iter().filter(|n| n.ends_with("_spectrum")) {
+                        if let Some(v) = self.lib.get_vec_info(&format!("{}.{}", plot, name)) {
+                            traces.push(Trace::new(
+                                name,
+                                "dB",
+                                "Hz",
+                                TraceSource::Noise,
+                                x.iter()
+                                    .zip(v.data_real().iter())
+                                    .map(|(x, y)| VSPoint::new(*x, 20.0 * y.log10() as f32))
+                                    .collect(),
+                            ));
+                        }
+                    }
+                }
+            } else if rows.is_empty() && vecs.iter().any(|n| n == "onoise_total") {
+                for name in vecs.iter().filter(|n| n.ends_with("_total")) {
+                    if let Some(v) = self.vec_value(&plot, name) {
+                        rows.push(ResultRow {
+                            name: name.clone(),
+                            value: format!("{:.4e}", v.re),
+                            device: None,
+                        });
+                    }
+                }
+            }
+        }
+        (traces, rows)
+    }
+    /// returns the poles and zeros from the current ngspice plot, which should be the result of a pz analysis,
+    /// as s-plane markers (x for poles, diamond for zeros) and as table rows
+    fn pz_results(&self) -> (Vec<Trace>, Vec<ResultRow>) {
+        let plot = self.lib.get_cur_plot();
+        let mut roots = vec![];
+        let mut rows = vec![];
+        for name in self.lib.get_all_vecs(&plot) {
+            if !(name.starts_with("pole") || name.starts_with("zero")) {
+                continue;
+            }
+            if let Some(v) = self.vec_value(&plot, &name) {
+                rows.push(ResultRow {
+                    value: format!("{:.4e} {:+.4e}j", v.re, v.im),
+                    name: name.clone(),
+                    device: None,
+                });
+                roots.push((name, v));
+            }
+        }
+        // marker size relative to the spread of the roots
+        let d = 0.03
+            * roots
+                .iter()
+                .map(|(_, v)| v.re.abs().max(v.im.abs()) as f32)
+                .fold(0.0, f32::max);
+        let d = if d > 0.0 { d } else { 1.0 };
+        let mut traces = vec![];
+        for (name, v) in roots {
+            let p = VSPoint::new(v.re as f32, v.im as f32);
+            let marker = |points| Trace::new(&name, "rad/s", "1/s", TraceSource::Pz, points);
+            if name.starts_with("pole") {
+                traces.push(marker(vec![
+                    VSPoint::new(p.x - d, p.y - d),
+                    VSPoint::new(p.x + d, p.y + d),
+                ]));
+                traces.push(marker(vec![
+                    VSPoint::new(p.x - d, p.y + d),
+                    VSPoint::new(p.x + d, p.y - d),
+                ]));
+            } else {
+                traces.push(marker(vec![
+                    VSPoint::new(p.x - d, p.y),
+                    VSPoint::new(p.x, p.y + d),
+                    VSPoint::new(p.x + d, p.y),
+                    VSPoint::new(p.x, p.y - d),
+                    VSPoint::new(p.x - d, p.y),
+                ]));
+            }
+        }
+        (traces, rows)
+    }
+    /// returns the gain, input and output impedance from the current ngspice plot, which should be the result of a tf analysis
+    fn tf_results(&self) -> Vec<ResultRow> {
+        let plot = self.lib.get_cur_plot();
+        self.lib
+            .get_all_vecs(&plot)
+            .into_iter()
+            .filter_map(|name| {
+                self.vec_value(&plot, &name).map(|v| ResultRow {
+                    value: format!("{:.4e}", v.re),
+                    // input impedance is reported as e.g. v1#input_impedance
+                    device: name
+                        .split_once("#input_impedance")
+                        .map(|(d, _)| d.to_string()),
+                    name,
+                })
+            })
+            .collect()
+    }
+    /// returns the sensitivities from the current ngspice plot, which should be the result of a sens analysis,
+    /// ranked by magnitude
+    fn sens_results(&self) -> Vec<ResultRow> {
+        let plot = self.lib.get_cur_plot();
+        let mut sens: Vec<_> = self
+            .lib
+            .get_all_vecs(&plot)
+            .into_iter()
+            .filter_map(|name| self.vec_value(&plot, &name).map(|v| (name, v.re)))
+            .collect();
+        sens.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        sens.into_iter()
+            .enumerate()
+            .map(|(i, (name, v))| ResultRow {
+                // model parameter sensitivities are reported as e.g. q1:bf
+                device: name
+                    .split(':')
+                    .next()
+                    .filter(|d| self.viewport.content.content.device_by_id(d).is_some())
+                    .map(|d| d.to_string()),
+                name: format!("{}. {}", i + 1, name),
+                value: format!("{:+.4e}", v),
+            })
+            .collect()
+    }
+    /// returns the names of the vectors associated with the selected elements, in lower case as reported by ngspice.
+    /// Net segments give the net voltage, devices give their branch current (voltage sources only).
+    fn selected_vec_names(&self) -> HashSet<String> {
+        self.viewport
+            .content
+            .selected()
+            .iter()
+            .filter_map(|atom| match atom {
+                CircuitAtom::NetEdge(e) => e.label.as_ref().map(|l| l.to_lowercase()),
+                CircuitAtom::RcRDevice(d) => {
+                    Some(format!("{}#branch", d.0.borrow().ng_id().to_lowercase()))
+                }
+                CircuitAtom::RcRLabel(_) | CircuitAtom::RcRMarker(_) | CircuitAtom::RcRProbe(_) => {
+                    None
+                }
+            })
+            .collect()
+    }
+    /// returns bode plot traces from the current ngspice plot, which should be the result of an ac analysis.
+    /// For every selected net (or every vector if nothing is selected), returns a magnitude trace (dB)
+    /// followed by a phase trace (degrees), both over frequency.
+    fn ac_traces(&self) -> Vec<Trace> {
+        let nets = self.selected_vec_names();
+        let plot = self.lib.get_cur_plot();
+        let mut traces = vec![];
+        if let Some(freq) = self.lib.get_vec_info(&format!("{}.frequency", plot)) {
+            let x: Vec<f32> = freq.data_real().iter().map(|f| *f as f32).collect();
+            for name in self.lib.get_all_vecs(&plot) {
+                if name == "frequency" || !(nets.is_empty() || nets.contains(&name.to_lowercase()))
+                {
+                    continue;
+                }
+                if let Some(v) = self.lib.get_vec_info(&format!("{}.{}", plot, name)) {
+                    let data = v.data_complex();
+                    traces.push(Trace::new(
+                        format!("{} mag", name),
+                        "dB",
+                        "Hz",
+                        TraceSource::Ac,
+                        x.iter()
+                            .zip(data.iter())
+                            .map(|(x, c)| VSPoint::new(*x, 20.0 * c.norm().log10() as f32))
+                            .collect(),
+                    ));
+                    traces.push(Trace::new(
+                        format!("{} phase", name),
+                        "deg",
+                        "Hz",
+                        TraceSource::Ac,
+                        x.iter()
+                            .zip(data.iter())
+                            .map(|(x, c)| VSPoint::new(*x, c.arg().to_degrees() as f32))
+                            .collect(),
+                    ));
+                }
+            }
+        }
+        traces
+    }
+    /// returns traces from the current ngspice plot, which should be the result of a dc analysis.
+    /// For every selected net (or every vector if nothing is selected), returns one trace over the inner sweep variable
+    /// for every value of the outer sweep variable.
+    fn dc_traces(&self) -> Vec<Trace> {
+        let names = self.selected_vec_names();
+        let plot = self.lib.get_cur_plot();
+        let vecs = self.lib.get_all_vecs(&plot);
+        let mut traces = vec![];
+        // ngspice names the dc scale vector after the swept quantity, e.g. v-sweep, temp-sweep
+        let opt_scale = vecs.iter().find(|n| n.ends_with("-sweep")).and_then(|n| {
+            self.lib
+                .get_vec_info(&format!("{}.{}", plot, n))
+                .map(|v| (trace::sweep_unit(n), v))
+        });
+        if let Some((x_unit, scale)) = opt_scale {
+            let x = scale.data_real();
+            // the inner sweep variable returns to its starting value with every step of the outer sweep
+            let inner_len = match x.first() {
+                Some(x0) => x
+                    .iter()
+                    .skip(1)
+                    .position(|v| v == x0)
+                    .map_or(x.len(), |p| p + 1),
+                None => return traces,
+            };
+            for name in &vecs {
+                if name.ends_with("-sweep")
+                    || !(names.is_empty() || names.contains(&name.to_lowercase()))
+                {
+                    continue;
+                }
+                if let Some(v) = self.lib.get_vec_info(&format!("{}.{}", plot, name)) {
+                    let y = v.data_real();
+                    for (xs, ys) in x.chunks(inner_len).zip(y.chunks(inner_len)) {
+                        traces.push(Trace::new(
+                            name,
+                            trace::unit_of(name),
+                            x_unit,
+                            TraceSource::Dc,
+                            xs.iter()
+                                .zip(ys.iter())
+                                .map(|(x, y)| VSPoint::new(*x as f32, *y as f32))
+                                .collect(),
+                        ));
+                    }
+                }
+            }
+        }
+        traces
+    }
+}
+
 impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
     fn update(&mut self, msg: CircuitPageMsg) {
         const NO_MODIFIER: Modifiers = Modifiers::empty();
+        if self.is_streaming() && starts_run(&msg) {
+            self.spmanager.console.lock().unwrap().push(
+                ConsoleSrc::Stat,
+                String::from("an analysis is running in the background, stop it first"),
+            );
+            return;
+        }
         match msg {
             CircuitPageMsg::ParamChanged(s) => {
+                self.param_error = match &self.active_element {
+                    Some(CircuitAtom::RcRDevice(d)) => {
+                        d.0.borrow().class().validate_param(&s).err()
+                    }
+                    _ => None,
+                };
                 self.param = s;
             }
             CircuitPageMsg::ParamSubmit => {
@@ -173,11 +1350,17 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                             d.0.borrow_mut()
                                 .class_mut()
                                 .set_raw_param(self.param.clone());
+                            self.viewport.content.content.evaluate_params();
                         }
                         CircuitAtom::RcRLabel(l) => {
                             l.0.borrow_mut().set_name(self.param.clone());
                         }
+                        CircuitAtom::RcRMarker(m) => {
+                            m.0.borrow_mut().set_value(self.param.clone());
+                        }
+                        CircuitAtom::RcRProbe(_) => {}
                     }
+                    self.viewport.content.content.mark_stale();
                     self.viewport.passive_cache.clear();
                 }
             }
@@ -191,20 +1374,54 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                         _,
                     ) => {
                         self.viewport.update(CompositeMsg {
-                            content_msg: schematic::Msg::ContentMsg(Msg::NetList),
+                            content_msg: schematic::Msg::ContentMsg(Msg::NetList(
+                                Variation::Nominal,
+                            )),
                             viewport_msg: viewport::Msg::None,
                         });
-                        self.lib.command("source netlist.cir"); // results pointer array starts at same address
-                        self.lib.command("op"); // ngspice recommends sending in control statements separately, not as part of netlist
-                        if let Some(pkvecvaluesall) =
-                            self.spmanager.vecvals.try_lock().unwrap().pop()
-                        {
-                            self.viewport.update(CompositeMsg {
-                                content_msg: schematic::Msg::ContentMsg(Msg::DcOp(
-                                    pkvecvaluesall.clone(),
-                                )),
-                                viewport_msg: viewport::Msg::None,
-                            });
+                        self.source_netlist(); // results pointer array starts at same address
+                        let converged = self.run_converging("op");
+                        let pkvecvaluesall = self.spmanager.vecvals.lock().unwrap().pop();
+                        match pkvecvaluesall.filter(|_| converged) {
+                            Some(pkvecvaluesall) => {
+                                self.last_op = pkvecvaluesall
+                                    .vecsa
+                                    .iter()
+                                    // node voltages only, device currents cannot be set as nodesets
+                                    .filter(|v| !v.name.contains('#') && !v.name.starts_with('@'))
+                                    .map(|v| (v.name.clone(), v.creal))
+                                    .collect();
+                                self.op_table.set_vecs(
+                                    pkvecvaluesall
+                                        .vecsa
+                                        .iter()
+                                        .map(|v| (v.name.as_str(), v.creal)),
+                                );
+                                self.viewport.update(CompositeMsg {
+                                    content_msg: schematic::Msg::ContentMsg(Msg::DcOp(
+                                        pkvecvaluesall.clone(),
+                                    )),
+                                    viewport_msg: viewport::Msg::None,
+                                });
+                                // device currents are looked up in the plot of the op just run
+                                let lib = &self.lib;
+                                self.device_op =
+                                    self.viewport.content.content.op_currents(|name| {
+                                        lib.get_vec_info(name)
+                                            .and_then(|v| v.data_complex().first().map(|c| c.re))
+                                    });
+                                self.results = device_op_rows(&self.device_op, self.op_sort);
+                                self.viewport.passive_cache.clear();
+                            }
+                            None => {
+                                self.device_op.clear();
+                                self.op_table.clear();
+                                // do not leave the annotations of a previous run
+                                self.viewport.update(CompositeMsg {
+                                    content_msg: schematic::Msg::ContentMsg(Msg::ClearOp),
+                                    viewport_msg: viewport::Msg::None,
+                                });
+                            }
                         }
                     }
                     schematic::Msg::Event(
@@ -215,15 +1432,15 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                         _,
                     ) => {
                         self.viewport.update(CompositeMsg {
-                            content_msg: schematic::Msg::ContentMsg(Msg::NetList),
+                            content_msg: schematic::Msg::ContentMsg(Msg::NetList(
+                                Variation::Nominal,
+                            )),
                             viewport_msg: viewport::Msg::None,
                         });
-                        self.lib.command("source netlist.cir"); // results pointer array starts at same address
-                        self.lib
-                            .command(&format!("ac lin 0 {} {}", self.ac_hz, self.ac_hz)); // ngspice recommends sending in control statements separately, not as part of netlist
-                        if let Some(pkvecvaluesall) =
-                            self.spmanager.vecvals.try_lock().unwrap().pop()
-                        {
+                        self.source_netlist(); // results pointer array starts at same address
+                        self.spmanager.vecvals.lock().unwrap().clear();
+                        self.analysis_command(&self.ac.command()); // ngspice recommends sending in control statements separately, not as part of netlist
+                        if let Some(pkvecvaluesall) = self.spmanager.vecvals.lock().unwrap().pop() {
                             self.viewport.update(CompositeMsg {
                                 content_msg: schematic::Msg::ContentMsg(Msg::Ac(
                                     pkvecvaluesall.clone(),
@@ -231,6 +1448,7 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                                 viewport_msg: viewport::Msg::None,
                             });
                         }
+                        self.traces = Some(self.ac_traces());
                     }
                     schematic::Msg::Event(
                         Event::Keyboard(iced::keyboard::Event::KeyPressed {
@@ -240,46 +1458,49 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                         _,
                     ) => {
                         self.viewport.update(CompositeMsg {
-                            content_msg: schematic::Msg::ContentMsg(Msg::NetList),
+                            content_msg: schematic::Msg::ContentMsg(Msg::NetList(
+                                Variation::Nominal,
+                            )),
                             viewport_msg: viewport::Msg::None,
                         });
-                        self.lib.command("source netlist.cir"); // results pointer array starts at same address
-                        self.spmanager.vecvals.try_lock().unwrap().clear();
-                        self.lib
-                            .command(&format!("tran {} {}", self.tran_step, self.tran_end)); // ngspice recommends sending in control statements separately, not as part of netlist
-
-                        let pk_results = self.spmanager.vecvals.try_lock().unwrap();
-
-                        let trace_count = pk_results.first().unwrap().count as usize;
-                        let mut results: Vec<Vec<VSPoint>> = Vec::with_capacity(trace_count);
-                        for _ in 0..trace_count {
-                            results.push(Vec::with_capacity(pk_results.len()));
-                        }
-
-                        let x_i = pk_results
-                            .first()
-                            .unwrap()
-                            .vecsa
-                            .iter()
-                            .position(|x| x.name == "time")
-                            .unwrap();
-                        for step_val in pk_results.iter() {
-                            for (trace_i, trace_val) in step_val.vecsa.iter().enumerate() {
-                                results[trace_i].push(VSPoint::new(
-                                    step_val.vecsa[x_i].creal as f32,
-                                    trace_val.creal as f32,
-                                ));
-                            }
+                        self.source_netlist(); // results pointer array starts at same address
+                        let cmd = self.run_command(RunAnalysisKind::Tran);
+                        // an empty plot rather than the traces of a previous run
+                        self.traces = Some(if self.run_converging(&cmd) {
+                            self.set_tran_steps();
+                            self.tran_traces()
+                        } else {
+                            self.tran_steps.clear();
+                            vec![]
+                        });
+                        if let Some((_, t1)) = self.tran_span() {
+                            self.scrub_time = t1;
                         }
-                        results.remove(x_i);
-
-                        self.traces = Some(results);
+                    }
+                    schematic::Msg::Event(
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::D,
+                            modifiers: iced::keyboard::Modifiers::SHIFT,
+                        }),
+                        _,
+                    ) => {
+                        self.viewport.update(CompositeMsg {
+                            content_msg: schematic::Msg::ContentMsg(Msg::NetList(
+                                Variation::Nominal,
+                            )),
+                            viewport_msg: viewport::Msg::None,
+                        });
+                        self.source_netlist(); // results pointer array starts at same address
+                        self.analysis_command(&self.dc.command()); // ngspice recommends sending in control statements separately, not as part of netlist
+                        self.traces = Some(self.dc_traces());
                     }
                     _ => {
                         self.viewport.update(msgs);
                     }
                 }
 
+                self.param_error = None;
+                self.tol_error = None;
                 match &self.viewport.content.active_element {
                     Some(ae) => {
                         self.active_element = Some(ae.clone());
@@ -287,21 +1508,328 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
                             CircuitAtom::NetEdge(_) => {}
                             CircuitAtom::RcRDevice(d) => {
                                 self.param = d.0.borrow().class().param_summary();
+                                self.param_error = d.0.borrow().class().param_error();
+                                self.tol =
+                                    d.0.borrow()
+                                        .tolerance()
+                                        .map(|t| (t * 100.0).to_string())
+                                        .unwrap_or_default();
                             }
                             CircuitAtom::RcRLabel(l) => {
                                 self.param = l.0.borrow().read().to_string();
                             }
+                            CircuitAtom::RcRMarker(m) => {
+                                self.param = m.0.borrow().value().to_string();
+                            }
+                            CircuitAtom::RcRProbe(_) => {}
                         }
                     }
-                    None => self.param = String::from(""),
+                    None => {
+                        self.param = String::from("");
+                        self.tol = String::from("");
+                    }
                 }
 
                 self.net_name = self.viewport.content.content.infobarstr.take();
             }
-            CircuitPageMsg::HzChanged(s) => self.ac_hz = s,
+            CircuitPageMsg::AcSweepSelected(sweep) => self.ac.sweep = sweep,
+            CircuitPageMsg::AcPointsChanged(s) => self.ac.points = s,
+            CircuitPageMsg::AcFstartChanged(s) => self.ac.fstart = s,
+            CircuitPageMsg::AcFstopChanged(s) => self.ac.fstop = s,
+            CircuitPageMsg::DcSrc1Changed(s) => self.dc.sweep1.src = s,
+            CircuitPageMsg::DcStart1Changed(s) => self.dc.sweep1.start = s,
+            CircuitPageMsg::DcStop1Changed(s) => self.dc.sweep1.stop = s,
+            CircuitPageMsg::DcStep1Changed(s) => self.dc.sweep1.step = s,
+            CircuitPageMsg::DcSrc2Changed(s) => self.dc.sweep2.src = s,
+            CircuitPageMsg::DcStart2Changed(s) => self.dc.sweep2.start = s,
+            CircuitPageMsg::DcStop2Changed(s) => self.dc.sweep2.stop = s,
+            CircuitPageMsg::DcStep2Changed(s) => self.dc.sweep2.step = s,
+            CircuitPageMsg::AnalysisSelected(kind) => self.analysis = kind,
+            CircuitPageMsg::SetupChanged(i, s) => self.setup_mut().set_field(i, s),
+            CircuitPageMsg::RunAnalysis => {
+                self.viewport.update(CompositeMsg {
+                    content_msg: schematic::Msg::ContentMsg(Msg::NetList(Variation::Nominal)),
+                    viewport_msg: viewport::Msg::None,
+                });
+                self.source_netlist(); // results pointer array starts at same address
+                let ok = self.analysis_command(&self.setup().command()); // ngspice recommends sending in control statements separately, not as part of netlist
+
+                // the results are read from the latest plots, which are those of an earlier run if this one failed,
+                // so a failure is reported instead
+                let error = match self.run_error() {
+                    None if !ok => Some(String::from("see the console")),
+                    error => error,
+                };
+                match error {
+                    Some(e) => {
+                        self.traces = Some(vec![]);
+                        self.results = vec![ResultRow {
+                            name: format!("{} failed", self.analysis),
+                            value: e,
+                            device: None,
+                        }];
+                    }
+                    None => match self.analysis {
+                        AnalysisKind::Noise => {
+                            let (traces, rows) = self.noise_results();
+                            self.traces = Some(traces);
+                            self.results = rows;
+                        }
+                        AnalysisKind::Pz => {
+                            let (traces, rows) = self.pz_results();
+                            self.traces = Some(traces);
+                            self.results = rows;
+                        }
+                        AnalysisKind::Tf => self.results = self.tf_results(),
+                        AnalysisKind::Sens => self.results = self.sens_results(),
+                    },
+                }
+            }
+            CircuitPageMsg::LocateDevice(id) => {
+                if let Some(d) = self.viewport.content.content.device_by_id(&id) {
+                    self.param = d.0.borrow().class().param_summary();
+                    self.param_error = d.0.borrow().class().param_error();
+                    self.active_element = Some(CircuitAtom::RcRDevice(d.clone()));
+                    self.viewport.content.select(CircuitAtom::RcRDevice(d));
+                    self.viewport.passive_cache.clear();
+                }
+            }
+            CircuitPageMsg::TolChanged(s) => {
+                self.tol_error = parse_tolerance(&s).err();
+                self.tol = s;
+            }
+            CircuitPageMsg::TolSubmit => {
+                if let Some(CircuitAtom::RcRDevice(d)) = &self.active_element {
+                    // an empty entry clears the tolerance, an invalid entry is kept for correction
+                    match parse_tolerance(&self.tol) {
+                        Ok(tol) => {
+                            self.tol_error = None;
+                            d.0.borrow_mut().set_tolerance(tol);
+                            self.viewport.passive_cache.clear();
+                        }
+                        Err(e) => self.tol_error = Some(e),
+                    }
+                }
+            }
+            CircuitPageMsg::McModeSelected(mode) => self.mc.mode = mode,
+            CircuitPageMsg::McAnalysisSelected(analysis) => self.mc.analysis = analysis,
+            CircuitPageMsg::McRunsChanged(s) => self.mc.runs = s,
+            CircuitPageMsg::McMeasChanged(s) => self.mc.meas = s,
+            CircuitPageMsg::McModelsChanged(s) => self.mc.models = s,
+            CircuitPageMsg::RunMonteCarlo => self.run_monte_carlo(),
+            CircuitPageMsg::StepTargetChanged(s) => self.step.target = s,
+            CircuitPageMsg::StepSweepSelected(sweep) => self.step.sweep = sweep,
+            CircuitPageMsg::StepValuesChanged(s) => self.step.values = s,
+            CircuitPageMsg::StepAnalysisSelected(analysis) => self.step.analysis = analysis,
+            CircuitPageMsg::RunStep => self.run_step(),
+            CircuitPageMsg::DesignParamNameChanged(i, s) => {
+                self.design_params_mut()[i].name = s;
+                self.design_params_changed();
+            }
+            CircuitPageMsg::DesignParamExprChanged(i, s) => {
+                self.design_params_mut()[i].expr = s;
+                self.design_params_changed();
+            }
+            CircuitPageMsg::ESeriesSelected(series) => self.eseries = series,
+            CircuitPageMsg::StdValuePicked(v) => {
+                self.param = eng::format(v);
+                self.param_error = None;
+                self.update(CircuitPageMsg::ParamSubmit);
+            }
+            CircuitPageMsg::SnapPassives => {
+                let changes = self.viewport.content.content.snap_passives(self.eseries);
+                self.results = if changes.is_empty() {
+                    vec![ResultRow {
+                        name: format!("snap to {}", self.eseries),
+                        value: String::from("no changes"),
+                        device: None,
+                    }]
+                } else {
+                    changes
+                        .into_iter()
+                        .map(|(id, old, new)| ResultRow {
+                            value: format!("{} -> {}", eng::format(old), eng::format(new)),
+                            name: id.clone(),
+                            device: Some(id),
+                        })
+                        .collect()
+                };
+                if let Some(CircuitAtom::RcRDevice(d)) = &self.active_element {
+                    self.param = d.0.borrow().class().param_summary();
+                }
+                self.viewport.passive_cache.clear();
+            }
+            CircuitPageMsg::OpSortSelected(sort) => {
+                self.op_sort = sort;
+                self.results = device_op_rows(&self.device_op, sort);
+            }
+            CircuitPageMsg::OpTableToggled(b) => self.show_op_table = b,
+            CircuitPageMsg::OpTableFilterChanged(s) => self.op_table.filter = s,
+            CircuitPageMsg::OpTableSortSelected(sort) => self.op_table.sort = sort,
+            CircuitPageMsg::OpTableExport => {
+                if let Err(e) = fs::write("op.csv", self.op_table.csv()) {
+                    self.results = vec![ResultRow {
+                        name: String::from("error"),
+                        value: format!("could not write op.csv: {}", e),
+                        device: None,
+                    }];
+                }
+            }
+            CircuitPageMsg::LocateNet(name) => {
+                let edges = self.viewport.content.content.net_edges(&name);
+                if !edges.is_empty() {
+                    self.active_element = None;
+                    self.viewport
+                        .content
+                        .select_all(edges.into_iter().map(CircuitAtom::NetEdge));
+                    self.viewport.passive_cache.clear();
+                }
+            }
+            CircuitPageMsg::ConsoleDockSelected(dock) => self.console_dock = dock,
+            CircuitPageMsg::ConsoleRunSelected(id) => {
+                // selecting the latest run follows new runs
+                let latest = self
+                    .spmanager
+                    .console
+                    .lock()
+                    .unwrap()
+                    .runs()
+                    .last()
+                    .map(|r| r.id);
+                self.console_run = if latest == Some(id) { None } else { Some(id) };
+            }
+            CircuitPageMsg::ConsoleFilterChanged(s) => self.console_filter = s,
+            CircuitPageMsg::ConsoleCopy => {
+                let console = self.spmanager.console.lock().unwrap();
+                if let Some(run) = console.run(self.console_run) {
+                    self.clipboard = Some(
+                        run.filtered(&self.console_filter)
+                            .map(|l| l.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+            }
+            CircuitPageMsg::ConsoleInputChanged(s) => self.console_input = s,
+            CircuitPageMsg::ConsoleSubmit => {
+                let cmd = std::mem::take(&mut self.console_input);
+                if !cmd.trim().is_empty() {
+                    self.console_run = None;
+                    self.command(cmd.trim());
+                }
+            }
+            CircuitPageMsg::KeepConvOptions => {
+                if let Some(opts) = self.convergence.take().and_then(|r| r.options) {
+                    *self.viewport.content.content.sim_options_mut() = opts;
+                }
+            }
+            CircuitPageMsg::DismissConv => self.convergence = None,
+            CircuitPageMsg::SimOptionChanged(name, s) => {
+                let opts = self.viewport.content.content.sim_options_mut();
+                if s.trim().is_empty() {
+                    opts.unset(name);
+                } else {
+                    opts.set(name, s.trim());
+                }
+            }
+            CircuitPageMsg::SimMethodSelected(method) => self
+                .viewport
+                .content
+                .content
+                .sim_options_mut()
+                .set_method(method),
+            CircuitPageMsg::SimTempChanged(s) => {
+                self.viewport.content.content.sim_options_mut().temp = s
+            }
+            CircuitPageMsg::SimClearNodesets => self
+                .viewport
+                .content
+                .content
+                .sim_options_mut()
+                .set_nodesets(vec![]),
+            CircuitPageMsg::OverrideAnalysisSelected(analysis) => self.override_analysis = analysis,
+            CircuitPageMsg::OverrideChanged(s) => self
+                .viewport
+                .content
+                .content
+                .sim_options_mut()
+                .set_override(self.override_analysis, s),
+            CircuitPageMsg::TempSweepChanged(s) => {
+                self.viewport.content.content.sim_options_mut().temp_sweep = s
+            }
+            CircuitPageMsg::TempSweepAnalysisSelected(analysis) => {
+                self.temp_sweep_analysis = analysis
+            }
+            CircuitPageMsg::RunTempSweep => self.run_temp_sweep(),
+            CircuitPageMsg::DesignParamAdd => {
+                self.design_params_mut().push(DesignParam::default());
+            }
+            CircuitPageMsg::DesignParamRemove(i) => {
+                self.design_params_mut().remove(i);
+                self.design_params_changed();
+            }
             CircuitPageMsg::StepChanged(s) => self.tran_step = s,
             CircuitPageMsg::TranChanged(s) => self.tran_end = s,
+            CircuitPageMsg::TranUicToggled(uic) => self.tran_uic = uic,
+            CircuitPageMsg::TranLive => {
+                self.viewport.update(CompositeMsg {
+                    content_msg: schematic::Msg::ContentMsg(Msg::NetList(Variation::Nominal)),
+                    viewport_msg: viewport::Msg::None,
+                });
+                self.source_netlist();
+                self.tran_steps.clear();
+                self.streaming = self.bg_analysis_command(&self.run_command(RunAnalysisKind::Tran));
+            }
+            CircuitPageMsg::TranStop => {
+                self.bg_command("halt");
+            }
+            CircuitPageMsg::McStop => {
+                // the run in progress is completed, and the results of the runs so far shown
+                if let Some(mc_run) = &mut self.mc_run {
+                    mc_run.pending.clear();
+                }
+                self.bg_command("halt");
+            }
+            CircuitPageMsg::StreamTick => {
+                if self.mc_run.is_some() && !self.spmanager.bg_running.load(Ordering::SeqCst) {
+                    self.mc_run_finished();
+                }
+                if self.streaming {
+                    if self.spmanager.bg_running.load(Ordering::SeqCst) {
+                        self.live_traces = Some(self.tran_traces());
+                    } else {
+                        // finished or halted: hand over the complete traces
+                        self.streaming = false;
+                        self.bg_analysis_finished(&self.run_command(RunAnalysisKind::Tran));
+                        self.traces = Some(self.tran_traces());
+                        self.set_tran_steps();
+                        if let Some((_, t1)) = self.tran_span() {
+                            self.scrub_time = t1;
+                        }
+                    }
+                }
+            }
+            CircuitPageMsg::TranScrubbed(t) => {
+                self.scrub_time = t;
+                if let Some(pkvecvaluesall) = self.tran_at(t) {
+                    self.viewport.update(CompositeMsg {
+                        content_msg: schematic::Msg::ContentMsg(Msg::TranAt(
+                            pkvecvaluesall,
+                            self.tran_range,
+                        )),
+                        viewport_msg: viewport::Msg::None,
+                    });
+                }
+            }
+            CircuitPageMsg::HeatmapToggled(on) => {
+                self.heatmap = on;
+                self.viewport.update(CompositeMsg {
+                    content_msg: schematic::Msg::ContentMsg(Msg::Heatmap(on)),
+                    viewport_msg: viewport::Msg::None,
+                });
+            }
         }
+        self.refresh_diagnostics();
     }
 
     fn view(&self) -> Element<CircuitPageMsg> {
@@ -331,10 +1859,24 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
             //     content_msg: schematic::Msg::ContentMsg(Msg::Wire),
             //     viewport_msg: viewport::Msg::None,
             // })),
-            text("ac freq (Hz): "),
-            text_input("", &self.ac_hz)
+            text("ac: "),
+            pick_list(
+                &AcSweep::ALL[..],
+                Some(self.ac.sweep),
+                CircuitPageMsg::AcSweepSelected
+            )
+            .width(70),
+            text_input("", &self.ac.points)
+                .width(40)
+                .on_input(CircuitPageMsg::AcPointsChanged),
+            text("from (Hz): "),
+            text_input("", &self.ac.fstart)
                 .width(50)
-                .on_input(CircuitPageMsg::HzChanged),
+                .on_input(CircuitPageMsg::AcFstartChanged),
+            text("to (Hz): "),
+            text_input("", &self.ac.fstop)
+                .width(50)
+                .on_input(CircuitPageMsg::AcFstopChanged),
             text("tran step (S): "),
             text_input("", &self.tran_step)
                 .width(50)
@@ -343,15 +1885,374 @@ impl IcedStruct<CircuitPageMsg> for CircuitSchematicPage {
             text_input("", &self.tran_end)
                 .width(50)
                 .on_input(CircuitPageMsg::TranChanged),
+            checkbox("uic", self.tran_uic, CircuitPageMsg::TranUicToggled),
+            if self.streaming {
+                button("stop").on_press(CircuitPageMsg::TranStop)
+            } else {
+                button("live").on_press(CircuitPageMsg::TranLive)
+            },
             text("Param: "),
             text_input("", &self.param)
                 .width(iced::Length::Fill)
                 .on_input(CircuitPageMsg::ParamChanged)
                 .on_submit(CircuitPageMsg::ParamSubmit),
+            text(self.param_error.as_deref().unwrap_or_default())
+                .style(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            text("Tol (%): "),
+            text_input("", &self.tol)
+                .width(40)
+                .on_input(CircuitPageMsg::TolChanged)
+                .on_submit(CircuitPageMsg::TolSubmit),
+            text(self.tol_error.as_deref().unwrap_or_default())
+                .style(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+        ]
+        .width(Length::Fill);
+
+        let dc_bar = row![
+            text("dc sweep: "),
+            text_input("source", &self.dc.sweep1.src)
+                .width(50)
+                .on_input(CircuitPageMsg::DcSrc1Changed),
+            text_input("start", &self.dc.sweep1.start)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStart1Changed),
+            text_input("stop", &self.dc.sweep1.stop)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStop1Changed),
+            text_input("step", &self.dc.sweep1.step)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStep1Changed),
+            text(" nested: "),
+            text_input("source", &self.dc.sweep2.src)
+                .width(50)
+                .on_input(CircuitPageMsg::DcSrc2Changed),
+            text_input("start", &self.dc.sweep2.start)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStart2Changed),
+            text_input("stop", &self.dc.sweep2.stop)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStop2Changed),
+            text_input("step", &self.dc.sweep2.step)
+                .width(50)
+                .on_input(CircuitPageMsg::DcStep2Changed),
+        ]
+        .width(Length::Fill);
+
+        let mut analysis_bar = row![pick_list(
+            &AnalysisKind::ALL[..],
+            Some(self.analysis),
+            CircuitPageMsg::AnalysisSelected
+        )
+        .width(70),]
+        .width(Length::Fill);
+        for (i, (label, value)) in self.setup().fields().into_iter().enumerate() {
+            analysis_bar = analysis_bar.push(
+                text_input(label, value)
+                    .width(70)
+                    .on_input(move |s| CircuitPageMsg::SetupChanged(i, s)),
+            );
+        }
+        analysis_bar = analysis_bar.push(button("run").on_press(CircuitPageMsg::RunAnalysis));
+
+        let mc_bar = row![
+            text("monte carlo: "),
+            pick_list(
+                &McMode::ALL[..],
+                Some(self.mc.mode),
+                CircuitPageMsg::McModeSelected
+            )
+            .width(90),
+            pick_list(
+                &RunAnalysisKind::ALL[..],
+                Some(self.mc.analysis),
+                CircuitPageMsg::McAnalysisSelected
+            )
+            .width(70),
+            text_input("runs", &self.mc.runs)
+                .width(40)
+                .on_input(CircuitPageMsg::McRunsChanged),
+            text_input("measure, e.g. vmax max v(net_1)", &self.mc.meas)
+                .width(200)
+                .on_input(CircuitPageMsg::McMeasChanged),
+            text_input("model variations, e.g. NMOS vto=0.7 10", &self.mc.models)
+                .width(Length::Fill)
+                .on_input(CircuitPageMsg::McModelsChanged),
+            if self.mc_run.is_some() {
+                button("stop").on_press(CircuitPageMsg::McStop)
+            } else {
+                button("run").on_press(CircuitPageMsg::RunMonteCarlo)
+            },
         ]
         .width(Length::Fill);
 
-        let schematic = iced::widget::column![canvas, infobar, toolbar];
+        let step_bar = row![
+            text("step: "),
+            text_input("R1, @m1[w] or param", &self.step.target)
+                .width(90)
+                .on_input(CircuitPageMsg::StepTargetChanged),
+            pick_list(
+                &StepSweep::ALL[..],
+                Some(self.step.sweep),
+                CircuitPageMsg::StepSweepSelected
+            )
+            .width(70),
+            text_input("values", &self.step.values)
+                .width(Length::Fill)
+                .on_input(CircuitPageMsg::StepValuesChanged),
+            pick_list(
+                &RunAnalysisKind::ALL[..],
+                Some(self.step.analysis),
+                CircuitPageMsg::StepAnalysisSelected
+            )
+            .width(70),
+            button("run").on_press(CircuitPageMsg::RunStep),
+        ]
+        .width(Length::Fill);
+
+        let design_params = self.viewport.content.content.design_params();
+        let mut params_bar = row![text("params: ")].width(Length::Fill).spacing(2);
+        for (i, (p, v)) in design_params
+            .params()
+            .iter()
+            .zip(design_params.evaluate())
+            .enumerate()
+        {
+            params_bar = params_bar.push(
+                text_input("name", &p.name)
+                    .width(60)
+                    .on_input(move |s| CircuitPageMsg::DesignParamNameChanged(i, s)),
+            );
+            params_bar = params_bar.push(text("="));
+            params_bar = params_bar.push(
+                text_input("value", &p.expr)
+                    .width(80)
+                    .on_input(move |s| CircuitPageMsg::DesignParamExprChanged(i, s)),
+            );
+            params_bar = params_bar.push(match v {
+                Ok(v) => text(format!("({})", eng::format(v))),
+                Err(e) => text(format!("({})", e)).style(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            });
+            params_bar =
+                params_bar.push(button("x").on_press(CircuitPageMsg::DesignParamRemove(i)));
+        }
+        params_bar = params_bar.push(button("+").on_press(CircuitPageMsg::DesignParamAdd));
+
+        // nearest standard values of the resistor or capacitor being edited
+        let std_values = match &self.active_element {
+            Some(CircuitAtom::RcRDevice(d)) => d.0.borrow().class().passive_value(&self.param),
+            _ => None,
+        }
+        .and_then(|v| self.eseries.neighbours(v));
+        let mut eseries_bar = row![
+            text("standard values: "),
+            pick_list(
+                &ESeries::ALL[..],
+                Some(self.eseries),
+                CircuitPageMsg::ESeriesSelected
+            )
+            .width(70),
+        ]
+        .width(Length::Fill)
+        .spacing(2);
+        if let Some((below, above)) = std_values {
+            eseries_bar = eseries_bar.push(
+                button(text(eng::format(below))).on_press(CircuitPageMsg::StdValuePicked(below)),
+            );
+            if above != below {
+                eseries_bar = eseries_bar.push(
+                    button(text(eng::format(above)))
+                        .on_press(CircuitPageMsg::StdValuePicked(above)),
+                );
+            }
+        }
+        eseries_bar = eseries_bar.push(
+            button(text(format!("snap R/C to {}", self.eseries)))
+                .on_press(CircuitPageMsg::SnapPassives),
+        );
+
+        let console_dock = row![
+            text("console: "),
+            pick_list(
+                &ConsoleDock::ALL[..],
+                Some(self.console_dock),
+                CircuitPageMsg::ConsoleDockSelected
+            )
+            .width(90),
+        ];
+        eseries_bar = eseries_bar.push(console_dock);
+        if !self.device_op.is_empty() {
+            eseries_bar = eseries_bar.push(row![
+                text(" op sort: "),
+                pick_list(
+                    &OpSort::ALL[..],
+                    Some(self.op_sort),
+                    CircuitPageMsg::OpSortSelected
+                )
+                .width(90),
+            ]);
+        }
+
+        let main_area: Element<CircuitPageMsg> = if self.results.is_empty() {
+            canvas
+        } else {
+            let rows: Vec<Element<CircuitPageMsg>> = self
+                .results
+                .iter()
+                .map(|r| {
+                    let label = text(format!("{}: {}", r.name, r.value)).size(14);
+                    match &r.device {
+                        // jump to the device on the schematic
+                        Some(id) => button(label)
+                            .on_press(CircuitPageMsg::LocateDevice(id.clone()))
+                            .into(),
+                        None => label.into(),
+                    }
+                })
+                .collect();
+            row![
+                canvas,
+                scrollable(Column::with_children(rows).spacing(2)).width(250)
+            ]
+            .into()
+        };
+
+        let main_area: Element<CircuitPageMsg> = if self.show_op_table && !self.op_table.is_empty()
+        {
+            row![main_area, self.op_table_view()].into()
+        } else {
+            main_area
+        };
+        eseries_bar = eseries_bar.push(checkbox(
+            "op table",
+            self.show_op_table,
+            CircuitPageMsg::OpTableToggled,
+        ));
+
+        let main_area: Element<CircuitPageMsg> = match self.console_dock {
+            ConsoleDock::Hidden => main_area,
+            ConsoleDock::Bottom => iced::widget::column![main_area, self.console_view()].into(),
+            ConsoleDock::Right => row![main_area, self.console_view()].into(),
+        };
+
+        let sim_options = self.viewport.content.content.sim_options();
+        let option_input = |name: &'static str, placeholder: &'static str| {
+            text_input(placeholder, sim_options.get(name).unwrap_or_default())
+                .width(60)
+                .on_input(move |s| CircuitPageMsg::SimOptionChanged(name, s))
+        };
+        let mut sim_bar = row![
+            text("sim options: reltol "),
+            option_input("reltol", "1e-3"),
+            text("abstol "),
+            option_input("abstol", "1e-12"),
+            text("vntol "),
+            option_input("vntol", "1e-6"),
+            text("method "),
+            pick_list(
+                &Method::ALL[..],
+                Some(sim_options.method()),
+                CircuitPageMsg::SimMethodSelected
+            )
+            .width(70),
+            text("temp (degC) "),
+            text_input("27", &sim_options.temp)
+                .width(50)
+                .on_input(CircuitPageMsg::SimTempChanged),
+            text("tnom "),
+            option_input("tnom", "27"),
+        ]
+        .width(Length::Fill)
+        .spacing(2);
+        if !sim_options.nodesets().is_empty() {
+            sim_bar = sim_bar.push(
+                button(text(format!(
+                    "clear nodeset ({} nets)",
+                    sim_options.nodesets().len()
+                )))
+                .on_press(CircuitPageMsg::SimClearNodesets),
+            );
+        }
+        let sim_bar2 = row![
+            text("override for "),
+            pick_list(
+                &OVERRIDE_ANALYSES[..],
+                Some(self.override_analysis),
+                CircuitPageMsg::OverrideAnalysisSelected
+            )
+            .width(70),
+            text_input(
+                "e.g. reltol=1e-4 method=gear",
+                sim_options.override_for(self.override_analysis)
+            )
+            .width(Length::Fill)
+            .on_input(CircuitPageMsg::OverrideChanged),
+            text("temp sweep (degC) "),
+            text_input("-40 27 85", &sim_options.temp_sweep)
+                .width(Length::Fill)
+                .on_input(CircuitPageMsg::TempSweepChanged),
+            pick_list(
+                &RunAnalysisKind::ALL[..],
+                Some(self.temp_sweep_analysis),
+                CircuitPageMsg::TempSweepAnalysisSelected
+            )
+            .width(70),
+            button("run").on_press(CircuitPageMsg::RunTempSweep),
+        ]
+        .width(Length::Fill)
+        .spacing(2);
+
+        let mut schematic = iced::widget::column![
+            main_area,
+            infobar,
+            toolbar,
+            dc_bar,
+            analysis_bar,
+            mc_bar,
+            step_bar,
+            params_bar,
+            eseries_bar,
+            sim_bar,
+            sim_bar2
+        ];
+        if let Some((t0, t1)) = self.tran_span().filter(|(t0, t1)| t1 > t0) {
+            let scrub_bar = row![
+                text("time "),
+                slider(t0..=t1, self.scrub_time, CircuitPageMsg::TranScrubbed)
+                    .step((t1 - t0) / 1000.0)
+                    .width(Length::Fill),
+                text(format!("{}s", eng::format(self.scrub_time as f64))).width(80),
+                checkbox("heatmap", self.heatmap, CircuitPageMsg::HeatmapToggled),
+            ]
+            .width(Length::Fill)
+            .spacing(4);
+            schematic = schematic.push(scrub_bar);
+        }
+        if let Some(report) = &self.convergence {
+            let mut conv_bar = row![].width(Length::Fill).spacing(2);
+            match &report.options {
+                Some(opts) => {
+                    conv_bar = conv_bar
+                        .push(text(format!(
+                            "`{}` converged with: {}",
+                            report.command, opts
+                        )))
+                        .push(
+                            button("keep in sim options").on_press(CircuitPageMsg::KeepConvOptions),
+                        );
+                }
+                None => {
+                    conv_bar = conv_bar.push(
+                        text(format!(
+                            "`{}` failed to converge with every strategy, see the console",
+                            report.command
+                        ))
+                        .style(iced::Color::from_rgb(1.0, 0.4, 0.4)),
+                    );
+                }
+            }
+            conv_bar = conv_bar.push(button("dismiss").on_press(CircuitPageMsg::DismissConv));
+            schematic = schematic.push(conv_bar);
+        }
 
         schematic.into()
     }
@@ -1,27 +1,48 @@
 //! Circuit
 //! Concrete types for schematic content
 
+use crate::schematic::atoms::NetEdge;
 use crate::schematic::atoms::NetVertex;
 use crate::schematic::atoms::RcRDevice;
 use crate::schematic::atoms::RcRLabel;
+use crate::schematic::atoms::Variation;
+use crate::schematic::atoms::{MarkerKind, RcRMarker};
+use crate::schematic::atoms::{ProbeKind, ProbeSignal, RcRProbe};
+use crate::schematic::design_params::DesignParams;
+use crate::schematic::eng;
+use crate::schematic::eseries::ESeries;
 use crate::schematic::layers::Devices;
 use crate::schematic::layers::NetLabels;
+use crate::schematic::layers::NetMarkers;
 use crate::schematic::layers::Nets;
+use crate::schematic::layers::Probes;
 use crate::schematic::models::NgModels;
+use crate::schematic::sim_options::SimOptions;
 use crate::schematic::{self, interactable::Interactive, SchematicMsg};
 use crate::transforms::VSPoint;
-use crate::transforms::{SSPoint, VCTransform, VSBox, VVTransform};
+use crate::transforms::{Point, SSPoint, VCTransform, VSBox, VSVec, VVTransform};
 use crate::Drawable;
 use iced::keyboard::Modifiers;
-use iced::widget::canvas::{event::Event, Frame};
+use iced::widget::canvas::{event::Event, stroke, Frame, LineCap, Path, Stroke, Text};
+use iced::Color;
 use paprika::PkVecvaluesall;
 use send_wrapper::SendWrapper;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use std::{collections::HashSet, fs};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
 
+mod analyses;
+mod console;
+mod convergence;
+mod diagnostics;
 mod gui;
+mod op_table;
+use analyses::DeviceOp;
+use diagnostics::{DiagTarget, Diagnostic};
 pub use gui::CircuitPageMsg;
 pub use gui::CircuitSchematicPage;
 
@@ -31,16 +52,25 @@ pub use atoms::CircuitAtom;
 use super::layers::DevicesLayer;
 use super::layers::DijkstraSt;
 use super::layers::NetLabelsLayer;
+use super::layers::NetMarkersLayer;
 use super::layers::NetsLayer;
+use super::layers::ProbesLayer;
 use super::layers::SchematicLayerEnum;
 
 #[derive(Debug, Clone)]
 pub enum Msg {
     CanvasEvent(Event),
     Wire,
-    NetList,
+    NetList(Variation),
     DcOp(PkVecvaluesall),
     Ac(PkVecvaluesall),
+    /// annotates devices and nets with the values at an instant of a transient analysis,
+    /// with the lowest and highest net voltage of the whole analysis for the heatmap
+    TranAt(PkVecvaluesall, Option<(f32, f32)>),
+    /// clears the operating point shown on devices
+    ClearOp,
+    /// colours nets by their voltage
+    Heatmap(bool),
 }
 
 impl schematic::ContentMsg for Msg {
@@ -71,6 +101,27 @@ pub struct Circuit {
     curpos_ssp: SSPoint,
 
     device_models: NgModels,
+
+    design_params: DesignParams,
+
+    sim_options: SimOptions,
+
+    /// signals of the probes, resolved when the netlist is created
+    probe_signals: Vec<ProbeSignal>,
+
+    /// ngspice errors of the latest simulation, attributed to devices and nets
+    diagnostics: Vec<Diagnostic>,
+
+    /// net voltages of the last op or ac analysis, by lowercase net name
+    net_results: HashMap<String, num::Complex<f32>>,
+    /// frequency of net_results if they are from an ac analysis: the last frequency of the sweep
+    results_ac: Option<f64>,
+    /// true if the circuit was edited after the last op or ac analysis
+    results_stale: bool,
+    /// colour nets by their voltage in net_results
+    heatmap: bool,
+    /// lowest and highest voltage of the heatmap colours, over the whole transient analysis. None to scale to net_results
+    heatmap_range: Option<(f32, f32)>,
 }
 
 impl Default for Circuit {
@@ -85,9 +136,20 @@ impl Default for Circuit {
                 SchematicLayerEnum::NetsLayer(NetsLayer::default()),
                 SchematicLayerEnum::DevicesLayer(DevicesLayer::default()),
                 SchematicLayerEnum::NetLabelsLayer(NetLabelsLayer::default()),
+                SchematicLayerEnum::NetMarkersLayer(NetMarkersLayer::default()),
+                SchematicLayerEnum::ProbesLayer(ProbesLayer::default()),
             ]),
             curpos_ssp: Default::default(),
             device_models: Default::default(),
+            design_params: Default::default(),
+            sim_options: Default::default(),
+            probe_signals: Default::default(),
+            diagnostics: Default::default(),
+            net_results: Default::default(),
+            results_ac: None,
+            heatmap_range: None,
+            results_stale: false,
+            heatmap: false,
         }
     }
 }
@@ -114,6 +176,20 @@ impl Circuit {
             panic!("labels layer should be in index 2");
         }
     }
+    fn markers_layer_mut(&mut self) -> &mut NetMarkers {
+        if let SchematicLayerEnum::NetMarkersLayer(markers) = &mut self.layers[3] {
+            &mut *markers
+        } else {
+            panic!("markers layer should be in index 3");
+        }
+    }
+    fn probes_layer_mut(&mut self) -> &mut Probes {
+        if let SchematicLayerEnum::ProbesLayer(probes) = &mut self.layers[4] {
+            &mut *probes
+        } else {
+            panic!("probes layer should be in index 4");
+        }
+    }
     fn nets_layer(&self) -> &Nets {
         if let SchematicLayerEnum::NetsLayer(nets) = &self.layers[0] {
             nets
@@ -135,12 +211,32 @@ impl Circuit {
             panic!("labels layer should be in index 2");
         }
     }
+    fn markers_layer(&self) -> &NetMarkers {
+        if let SchematicLayerEnum::NetMarkersLayer(markers) = &self.layers[3] {
+            markers
+        } else {
+            panic!("markers layer should be in index 3");
+        }
+    }
+    fn probes_layer(&self) -> &Probes {
+        if let SchematicLayerEnum::ProbesLayer(probes) = &self.layers[4] {
+            probes
+        } else {
+            panic!("probes layer should be in index 4");
+        }
+    }
     pub fn curpos_ssp(&self) -> SSPoint {
         self.curpos_ssp
     }
     fn update_cursor_vsp(&mut self, curpos_vsp: VSPoint) {
         self.curpos_ssp = curpos_vsp.round().cast().cast_unit();
-        self.infobarstr = self.nets_layer().net_name_at(self.curpos_ssp);
+        self.infobarstr =
+            self.nets_layer()
+                .net_name_at(self.curpos_ssp)
+                .map(|n| match self.net_result(&n) {
+                    Some(v) => format!("{} {}", n, v),
+                    None => n,
+                });
         let mut ns = self.state.clone();
         match &self.state {
             CircuitSt::Wiring(Some((_nets_og, dijkstrast))) => {
@@ -164,7 +260,10 @@ impl Circuit {
                             // do not go over NetVertex at any cost
                             return f32::INFINITY;
                         }
-                        if self.labels_layer().any_occupy_ssp(next) {
+                        if self.labels_layer().any_occupy_ssp(next)
+                            || self.markers_layer().any_occupy_ssp(next)
+                            || self.probes_layer().any_occupy_ssp(next)
+                        {
                             // do not go over NetLabel at any cost
                             return f32::INFINITY;
                         }
@@ -202,8 +301,12 @@ impl Circuit {
 impl Drawable for Circuit {
     fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
         self.nets_layer().draw_persistent(vct, vcscale, frame);
+        self.draw_heatmap(vct, frame);
         self.devices_layer().draw_persistent(vct, vcscale, frame);
         self.labels_layer().draw_persistent(vct, vcscale, frame);
+        self.markers_layer().draw_persistent(vct, vcscale, frame);
+        self.probes_layer().draw_persistent(vct, vcscale, frame);
+        self.draw_diagnostics(vct, frame);
     }
 
     fn draw_selected(&self, _vct: VCTransform, _vcscale: f32, _frame: &mut Frame) {
@@ -218,6 +321,37 @@ impl Drawable for Circuit {
             CircuitSt::Idle => {}
             _ => {}
         }
+        // show the diagnostic message of the element under the cursor
+        if let Some(msg) = self.diagnostic_at(self.curpos_ssp) {
+            let a = Text {
+                content: msg.to_string(),
+                position: Point::from(
+                    vct.transform_point(self.curpos_ssp.cast().cast_unit() + VSVec::new(0.5, 0.5)),
+                )
+                .into(),
+                color: Color::from_rgb(1.0, 0.4, 0.4),
+                size: vcscale.max(14.0),
+                ..Default::default()
+            };
+            frame.fill_text(a);
+        } else if let Some((name, i)) = self.devices_layer().op_current_at(self.curpos_ssp) {
+            // show the current into the device terminal under the cursor
+            let mut content = format!("{}={}A", name, eng::format(i));
+            if self.results_stale {
+                content.push_str(" (stale)");
+            }
+            let a = Text {
+                content,
+                position: Point::from(
+                    vct.transform_point(self.curpos_ssp.cast().cast_unit() + VSVec::new(0.5, 0.5)),
+                )
+                .into(),
+                color: Color::from_rgb(0.6, 1.0, 0.6),
+                size: vcscale.max(14.0),
+                ..Default::default()
+            };
+            frame.fill_text(a);
+        }
     }
 }
 
@@ -232,7 +366,9 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
         let bbn = self.nets_layer().bounding_box();
         let bbi = self.devices_layer().bounding_box();
         let bbl = self.labels_layer().bounding_box();
-        bbn.union(&bbi).union(&bbl)
+        let bbm = self.markers_layer().bounding_box();
+        let bbp = self.probes_layer().bounding_box();
+        bbn.union(&bbi).union(&bbl).union(&bbm).union(&bbp)
     }
     fn intersects_vsb(&mut self, vsb: VSBox) -> HashSet<CircuitAtom> {
         let mut ret = HashSet::new();
@@ -258,6 +394,12 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
         for rcrl in self.labels_layer().contained_by(&vsb) {
             ret.insert(CircuitAtom::RcRLabel(rcrl));
         }
+        for rcrm in self.markers_layer().contained_by(&vsb) {
+            ret.insert(CircuitAtom::RcRMarker(rcrm));
+        }
+        for rcrp in self.probes_layer().contained_by(&vsb) {
+            ret.insert(CircuitAtom::RcRProbe(rcrp));
+        }
         ret
     }
 
@@ -267,6 +409,12 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
         if let Some(l) = self.labels_layer_mut().selectable(vsp, skip, count) {
             return Some(CircuitAtom::RcRLabel(l));
         }
+        if let Some(m) = self.markers_layer_mut().selectable(vsp, skip, count) {
+            return Some(CircuitAtom::RcRMarker(m));
+        }
+        if let Some(p) = self.probes_layer_mut().selectable(vsp, skip, count) {
+            return Some(CircuitAtom::RcRProbe(p));
+        }
         if let Some(e) = self.nets_layer().selectable(vsp, skip, count) {
             return Some(CircuitAtom::NetEdge(e));
         }
@@ -306,10 +454,12 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                             } else if self.electrically_occupies_ssp(ssp) {
                                 let extra_vertices = self.devices_layer().ports_ssp();
                                 self.nets_layer_mut().merge(g.as_ref(), &extra_vertices);
+                                self.mark_stale();
                                 new_ws = None;
                             } else {
                                 let extra_vertices = self.devices_layer().ports_ssp();
                                 self.nets_layer_mut().merge(g.as_ref(), &extra_vertices);
+                                self.mark_stale();
                                 new_ws = Some((Box::new(Nets::new()), DijkstraSt::new(ssp)));
                             }
                             ret_msg_tmp = SchematicMsg::ClearPassive;
@@ -331,6 +481,63 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                         ret_msg_tmp =
                             SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRLabel(l)));
                     }
+                    // initial condition and nodeset markers
+                    (
+                        CircuitSt::Idle,
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::I,
+                            modifiers: Modifiers::SHIFT,
+                        }),
+                    ) => {
+                        let m = NetMarkers::new_marker(MarkerKind::Ic);
+                        ret_msg_tmp =
+                            SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRMarker(m)));
+                    }
+                    (
+                        CircuitSt::Idle,
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::N,
+                            modifiers: Modifiers::SHIFT,
+                        }),
+                    ) => {
+                        let m = NetMarkers::new_marker(MarkerKind::Nodeset);
+                        ret_msg_tmp =
+                            SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRMarker(m)));
+                    }
+                    // probes
+                    (
+                        CircuitSt::Idle,
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::V,
+                            modifiers: Modifiers::SHIFT,
+                        }),
+                    ) => {
+                        let p = Probes::new_probe(ProbeKind::Voltage);
+                        ret_msg_tmp =
+                            SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRProbe(p)));
+                    }
+                    (
+                        CircuitSt::Idle,
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::B,
+                            modifiers: Modifiers::SHIFT,
+                        }),
+                    ) => {
+                        let p = Probes::new_probe(ProbeKind::Differential);
+                        ret_msg_tmp =
+                            SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRProbe(p)));
+                    }
+                    (
+                        CircuitSt::Idle,
+                        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            key_code: iced::keyboard::KeyCode::A,
+                            modifiers: Modifiers::SHIFT,
+                        }),
+                    ) => {
+                        let p = Probes::new_probe(ProbeKind::Current);
+                        ret_msg_tmp =
+                            SchematicMsg::NewElement(SendWrapper::new(CircuitAtom::RcRProbe(p)));
+                    }
                     // device placement
                     (
                         CircuitSt::Idle,
@@ -446,8 +653,8 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                 self.state = state;
                 ret_msg_tmp
             }
-            Msg::NetList => {
-                self.netlist();
+            Msg::NetList(variation) => {
+                self.netlist(&variation);
                 SchematicMsg::None
             }
             Msg::Wire => {
@@ -456,10 +663,27 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
             }
             Msg::DcOp(pkvecvaluesall) => {
                 self.devices_layer_mut().op(&pkvecvaluesall);
+                self.set_net_results(&pkvecvaluesall, false);
                 SchematicMsg::ClearPassive
             }
             Msg::Ac(pkvecvaluesall) => {
                 self.devices_layer_mut().op(&pkvecvaluesall);
+                self.set_net_results(&pkvecvaluesall, true);
+                SchematicMsg::ClearPassive
+            }
+            Msg::TranAt(pkvecvaluesall, range) => {
+                self.devices_layer_mut().op(&pkvecvaluesall);
+                self.set_net_results(&pkvecvaluesall, false);
+                self.heatmap_range = range;
+                SchematicMsg::ClearPassive
+            }
+            Msg::ClearOp => {
+                self.devices_layer_mut().clear_op();
+                self.net_results.clear();
+                SchematicMsg::ClearPassive
+            }
+            Msg::Heatmap(on) => {
+                self.heatmap = on;
                 SchematicMsg::ClearPassive
             }
         };
@@ -485,6 +709,18 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                     // inserts the label if placing a new label
                     self.labels_layer_mut().insert(l.clone());
                 }
+                CircuitAtom::RcRMarker(m) => {
+                    m.0.borrow_mut().transform(*sst);
+                    // if moving an existing marker, does nothing
+                    // inserts the marker if placing a new marker
+                    self.markers_layer_mut().insert(m.clone());
+                }
+                CircuitAtom::RcRProbe(p) => {
+                    p.0.borrow_mut().transform(*sst);
+                    // if moving an existing probe, does nothing
+                    // inserts the probe if placing a new probe
+                    self.probes_layer_mut().insert(p.clone());
+                }
             }
         }
         for n in nets {
@@ -502,6 +738,7 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                 .graph
                 .add_edge(NetVertex(n1.src), NetVertex(n1.dst), n1);
         }
+        self.mark_stale();
         self.prune();
     }
 
@@ -544,8 +781,29 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                     self.labels_layer_mut().insert(rcr_label.clone());
                     elements.insert(CircuitAtom::RcRLabel(rcr_label));
                 }
+                CircuitAtom::RcRMarker(rcm) => {
+                    //unwrap refcell
+                    let mut marker = (*rcm.0.borrow()).clone();
+                    marker.transform(*sst);
+
+                    //build BaseElement
+                    let rcr_marker = RcRMarker(Rc::new(RefCell::new(marker)));
+                    self.markers_layer_mut().insert(rcr_marker.clone());
+                    elements.insert(CircuitAtom::RcRMarker(rcr_marker));
+                }
+                CircuitAtom::RcRProbe(rcp) => {
+                    //unwrap refcell
+                    let mut probe = (*rcp.0.borrow()).clone();
+                    probe.transform(*sst);
+
+                    //build BaseElement
+                    let rcr_probe = RcRProbe(Rc::new(RefCell::new(probe)));
+                    self.probes_layer_mut().insert(rcr_probe.clone());
+                    elements.insert(CircuitAtom::RcRProbe(rcr_probe));
+                }
             }
         }
+        self.mark_stale();
     }
 
     fn delete_elements(&mut self, elements: &HashSet<CircuitAtom>) {
@@ -560,8 +818,15 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
                 CircuitAtom::RcRLabel(l) => {
                     self.labels_layer_mut().delete_item(l);
                 }
+                CircuitAtom::RcRMarker(m) => {
+                    self.markers_layer_mut().delete_item(m);
+                }
+                CircuitAtom::RcRProbe(p) => {
+                    self.probes_layer_mut().delete_item(p);
+                }
             }
         }
+        self.mark_stale();
         self.prune();
     }
 
@@ -571,10 +836,13 @@ impl schematic::Content<CircuitAtom, Msg> for Circuit {
 }
 
 impl Circuit {
-    /// create netlist for the current schematic and save it.
-    pub fn netlist(&mut self) {
+    /// create netlist for the current schematic and save it. Values with a tolerance are varied according to variation.
+    pub fn netlist(&mut self, variation: &Variation) {
         let mut netlist = String::from("Netlist Created by Circe\n");
-        netlist.push_str(&self.device_models.model_definitions());
+        let mut idx = 0;
+        netlist.push_str(&self.device_models.model_definitions(variation, &mut idx));
+        netlist.push_str(&self.design_params.param_definitions());
+        netlist.push_str(&self.sim_options.definitions());
         if self.devices_layer().get_set().is_empty() {
             // empty netlist
             netlist.push_str("V_0 0 n1 0"); // give it something so spice doesnt hang
@@ -583,11 +851,344 @@ impl Circuit {
 
         self.prune();
         for d in self.devices_layer().get_set() {
-            netlist.push_str(&d.0.borrow_mut().spice_line(self.nets_layer()));
+            netlist.push_str(
+                &d.0.borrow_mut()
+                    .spice_line(self.nets_layer(), variation, &mut idx),
+            );
         }
+        netlist.push_str(&self.markers_layer().spice_lines(self.nets_layer()));
+        self.probe_signals = self
+            .probes_layer()
+            .signals(self.nets_layer(), self.devices_layer());
         netlist.push('\n');
         fs::write("netlist.cir", netlist.as_bytes()).expect("Unable to write file");
     }
+    /// returns the number of values which are varied in monte carlo and corner analyses
+    pub fn variations_count(&self) -> usize {
+        self.device_models.variations_count()
+            + self
+                .devices_layer()
+                .get_set()
+                .iter()
+                .filter(|d| {
+                    let d = d.0.borrow();
+                    d.tolerance().is_some() && d.class().value_token_index().is_some()
+                })
+                .count()
+    }
+    /// returns a reference to the design parameters
+    pub fn design_params(&self) -> &DesignParams {
+        &self.design_params
+    }
+    /// returns a mut reference to the design parameters. Call `evaluate_params` after making changes
+    pub fn design_params_mut(&mut self) -> &mut DesignParams {
+        &mut self.design_params
+    }
+    /// evaluates the `{expressions}` in the parameters of every device, for display on canvas
+    pub fn evaluate_params(&self) {
+        for d in self.devices_layer().get_set() {
+            let summary = d.0.borrow().class().param_summary();
+            d.0.borrow_mut()
+                .set_expr_value(self.design_params.substitute(&summary));
+        }
+    }
+    /// returns the signals of the probes, as resolved when the netlist was last created. Empty if there are no probes
+    pub fn probe_signals(&self) -> &[ProbeSignal] {
+        &self.probe_signals
+    }
+    /// returns the ngspice command saving only the probed signals and the node voltages, if any probes are placed.
+    /// Transient analyses are run with it, the node voltages kept for the time scrubber and heatmap.
+    /// Other analyses save every vector for the annotations and tables
+    pub fn probe_save_command(&self) -> Option<String> {
+        if self.probe_signals.is_empty() {
+            return None;
+        }
+        let nets = self.net_names();
+        let mut vecs: Vec<&str> = self
+            .probe_signals
+            .iter()
+            .flat_map(|s| std::iter::once(s.vec.as_str()).chain(s.ref_vec.as_deref()))
+            .chain(nets.iter().map(|n| n.as_str()))
+            .collect();
+        vecs.sort();
+        vecs.dedup();
+        Some(format!("save {}", vecs.join(" ")))
+    }
+    /// returns the ngspice command saving the device terminal currents along with the default vectors, if any device has such currents.
+    /// Operating point analyses are run with it for the current and power annotations
+    pub fn op_save_command(&self) -> Option<String> {
+        let mut vecs: Vec<String> = self
+            .devices_layer()
+            .get_set()
+            .iter()
+            .flat_map(|d| {
+                let d = d.0.borrow();
+                let id = d.ng_id();
+                (0..d.ports_ssp().len())
+                    .filter_map(|port| d.class().terminal_current(&id, port))
+                    .map(|(vec, _, _)| vec)
+                    // branch currents are saved by default
+                    .filter(|vec| vec.starts_with('@'))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if vecs.is_empty() {
+            return None;
+        }
+        vecs.sort();
+        vecs.dedup();
+        Some(format!("save all {}", vecs.join(" ")))
+    }
+    /// sets the current into every device terminal from query, which returns the value of an ngspice vector
+    /// after an operating point analysis. Returns the current and power of every device, sorted by netlist identifier
+    pub fn op_currents(&self, query: impl Fn(&str) -> Option<f64>) -> Vec<DeviceOp> {
+        let mut ops: Vec<DeviceOp> = self
+            .devices_layer()
+            .get_set()
+            .iter()
+            .map(|d| {
+                let mut d = d.0.borrow_mut();
+                let id = d.ng_id();
+                let currents = (0..d.ports_ssp().len())
+                    .map(|port| {
+                        let (vec, _, sign) = d.class().terminal_current(&id, port)?;
+                        query(&vec).map(|i| i * sign)
+                    })
+                    .collect();
+                d.set_op_currents(currents);
+                DeviceOp {
+                    id,
+                    current: d.op_current(),
+                    power: d.op_power(),
+                }
+            })
+            .collect();
+        ops.sort_by(|a, b| a.id.cmp(&b.id));
+        ops
+    }
+    /// returns a reference to the simulation options
+    pub fn sim_options(&self) -> &SimOptions {
+        &self.sim_options
+    }
+    /// returns a mut reference to the simulation options
+    pub fn sim_options_mut(&mut self) -> &mut SimOptions {
+        &mut self.sim_options
+    }
+    /// rounds every resistor and capacitor to the nearest standard value of series.
+    /// Returns the id, old and new value of every changed device
+    pub fn snap_passives(&mut self, series: ESeries) -> Vec<(String, f64, f64)> {
+        let changes = self.devices_layer().snap_to_series(series);
+        self.evaluate_params();
+        if !changes.is_empty() {
+            self.mark_stale();
+        }
+        changes
+    }
+    /// returns a mut reference to the device models
+    pub fn models_mut(&mut self) -> &mut NgModels {
+        &mut self.device_models
+    }
+    /// returns the device with netlist identifier id (case insensitive), if any
+    pub fn device_by_id(&self, id: &str) -> Option<RcRDevice> {
+        self.devices_layer()
+            .get_set()
+            .iter()
+            .find(|d| d.0.borrow().ng_id().eq_ignore_ascii_case(id))
+            .cloned()
+    }
+    /// stores the net voltages of an op or ac analysis, for display when hovering over a net
+    fn set_net_results(&mut self, pkvecvaluesall: &PkVecvaluesall, ac: bool) {
+        self.net_results = pkvecvaluesall
+            .vecsa
+            .iter()
+            .map(|v| (v.name.to_lowercase(), (*v.as_ref()).clone().into()))
+            .collect();
+        self.heatmap_range = None;
+        self.results_ac = pkvecvaluesall
+            .vecsa
+            .iter()
+            .find(|v| v.name == "frequency")
+            .map(|v| v.creal)
+            .filter(|_| ac);
+        self.results_stale = false;
+    }
+    /// marks the simulation results as stale, after the circuit is edited
+    pub fn mark_stale(&mut self) {
+        self.results_stale = true;
+    }
+    /// returns the voltage of net from the last op or ac analysis, formatted for display
+    fn net_result(&self, net: &str) -> Option<String> {
+        let v = if net == "0" {
+            num::Complex::default()
+        } else {
+            *self.net_results.get(&net.to_lowercase())?
+        };
+        let mut s = if let Some(f) = self.results_ac {
+            format!(
+                "|V|={}V {:.1}deg @ {}Hz",
+                eng::format(v.norm() as f64),
+                v.arg().to_degrees(),
+                eng::format(f)
+            )
+        } else {
+            format!("V={}V", eng::format(v.re as f64))
+        };
+        if self.results_stale {
+            s.push_str(" (stale)");
+        }
+        Some(s)
+    }
+    /// returns the names of the nets other than ground, as the ngspice vectors of their voltages
+    pub fn net_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .nets_layer()
+            .graph
+            .all_edges()
+            .filter_map(|e| e.2.label.as_ref().map(|l| l.to_lowercase()))
+            .filter(|l| l != "0")
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+    /// returns the net segments of the net named name
+    pub fn net_edges(&self, name: &str) -> Vec<NetEdge> {
+        self.nets_layer()
+            .graph
+            .all_edges()
+            .filter(|e| {
+                e.2.label
+                    .as_ref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(name))
+            })
+            .map(|e| e.2.clone())
+            .collect()
+    }
+    /// attributes the ngspice error messages in lines to the devices and nets they mention, replacing previous diagnostics
+    pub fn diagnose(&mut self, lines: &[String]) {
+        let devices: Vec<String> = self
+            .devices_layer()
+            .get_set()
+            .iter()
+            .map(|d| d.0.borrow().ng_id())
+            .collect();
+        let mut nets: Vec<String> = self
+            .nets_layer()
+            .graph
+            .all_edges()
+            .filter_map(|e| e.2.label.as_ref().map(|l| l.to_string()))
+            .collect();
+        nets.sort();
+        nets.dedup();
+        self.diagnostics = diagnostics::diagnose(lines, &devices, &nets);
+    }
+    /// returns the diagnostic message of the device or net at ssp, if any
+    fn diagnostic_at(&self, ssp: SSPoint) -> Option<&str> {
+        let device = self
+            .devices_layer()
+            .get_set()
+            .iter()
+            .find(|d| d.0.borrow().interactable.contains_ssp(ssp))
+            .map(|d| DiagTarget::Device(d.0.borrow().ng_id().to_lowercase()));
+        let net = self
+            .nets_layer()
+            .net_name_at(ssp)
+            .map(|n| DiagTarget::Net(n.to_lowercase()));
+        [device, net]
+            .into_iter()
+            .flatten()
+            .find_map(|t| self.diagnostics.iter().find(|d| d.target == t))
+            .map(|d| d.message.as_str())
+    }
+    /// outlines the devices and net segments with diagnostics
+    fn draw_diagnostics(&self, vct: VCTransform, frame: &mut Frame) {
+        if self.diagnostics.is_empty() {
+            return;
+        }
+        let diag_stroke = Stroke {
+            width: 2.0,
+            style: stroke::Style::Solid(Color::from_rgb(1.0, 0.2, 0.2)),
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+        for d in self.devices_layer().get_set() {
+            let d = d.0.borrow();
+            let target = DiagTarget::Device(d.ng_id().to_lowercase());
+            if self.diagnostics.iter().any(|diag| diag.target == target) {
+                let csb = vct.outer_transformed_box(&d.interactable.bounds);
+                let c = Path::rectangle(
+                    iced::Point::from([csb.min.x, csb.min.y]),
+                    iced::Size::from([csb.width(), csb.height()]),
+                );
+                frame.stroke(&c, diag_stroke.clone());
+            }
+        }
+        for e in self.nets_layer().graph.all_edges() {
+            let Some(label) = &e.2.label else {
+                continue;
+            };
+            let target = DiagTarget::Net(label.to_lowercase());
+            if self.diagnostics.iter().any(|diag| diag.target == target) {
+                let src = vct.transform_point(e.2.src.cast().cast_unit());
+                let dst = vct.transform_point(e.2.dst.cast().cast_unit());
+                let c = Path::line(
+                    iced::Point::from([src.x, src.y]),
+                    iced::Point::from([dst.x, dst.y]),
+                );
+                frame.stroke(&c, diag_stroke.clone());
+            }
+        }
+    }
+    /// draws the nets in colours from blue at the lowest voltage to red at the highest, if the heatmap is on
+    fn draw_heatmap(&self, vct: VCTransform, frame: &mut Frame) {
+        if !self.heatmap || self.results_ac.is_some() {
+            return;
+        }
+        let voltage = |label: &str| {
+            if label == "0" {
+                Some(0.0)
+            } else {
+                self.net_results.get(&label.to_lowercase()).map(|v| v.re)
+            }
+        };
+        let edges: Vec<_> = self
+            .nets_layer()
+            .graph
+            .all_edges()
+            .filter_map(|e| {
+                let v = voltage(e.2.label.as_ref()?)?;
+                Some((e.2.src, e.2.dst, v))
+            })
+            .collect();
+        let (min, max) = self.heatmap_range.unwrap_or_else(|| {
+            (
+                edges.iter().map(|e| e.2).fold(f32::INFINITY, f32::min),
+                edges.iter().map(|e| e.2).fold(f32::NEG_INFINITY, f32::max),
+            )
+        });
+        for (src, dst, v) in edges {
+            let t = if max > min {
+                ((v - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            let src = vct.transform_point(src.cast().cast_unit());
+            let dst = vct.transform_point(dst.cast().cast_unit());
+            let c = Path::line(
+                iced::Point::from([src.x, src.y]),
+                iced::Point::from([dst.x, dst.y]),
+            );
+            frame.stroke(
+                &c,
+                Stroke {
+                    width: 4.0,
+                    style: stroke::Style::Solid(Color::from_rgba(t, 0.2, 1.0 - t, 0.8)),
+                    line_cap: LineCap::Round,
+                    ..Stroke::default()
+                },
+            );
+        }
+    }
     /// clear up nets graph: merging segments, cleaning up segment net names, etc.
     fn prune(&mut self) {
         let extra_vertices = self.devices_layer().ports_ssp();
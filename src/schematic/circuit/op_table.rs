@@ -0,0 +1,210 @@
+//! operating point table
+//! lists the node voltages and branch currents of the last dc operating point
+
+use std::fmt::Display;
+
+use crate::schematic::eng;
+
+/// quantity of an operating point vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpQuantity {
+    /// node voltage
+    Voltage,
+    /// branch or device current
+    Current,
+}
+
+impl OpQuantity {
+    /// returns the unit of the quantity
+    fn unit(&self) -> &'static str {
+        match self {
+            OpQuantity::Voltage => "V",
+            OpQuantity::Current => "A",
+        }
+    }
+}
+
+/// a row of the operating point table
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpRow {
+    /// ngspice vector name, e.g. `net_1`, `v1#branch` or `@r1[i]`
+    pub name: String,
+    /// value of the vector
+    pub value: f64,
+    /// whether the vector is a voltage or current
+    pub quantity: OpQuantity,
+}
+
+impl OpRow {
+    /// returns a row for the ngspice vector name with value
+    fn new(name: &str, value: f64) -> Self {
+        let quantity = if name.contains("#branch") || name.starts_with('@') {
+            OpQuantity::Current
+        } else {
+            OpQuantity::Voltage
+        };
+        OpRow {
+            name: name.to_string(),
+            value,
+            quantity,
+        }
+    }
+    /// returns the value in engineering notation with its unit
+    pub fn formatted(&self) -> String {
+        format!("{}{}", eng::format(self.value), self.quantity.unit())
+    }
+    /// returns the netlist identifier of the device a current belongs to
+    pub fn device(&self) -> Option<&str> {
+        match self.quantity {
+            OpQuantity::Voltage => None,
+            OpQuantity::Current => match self.name.strip_prefix('@') {
+                // @r1[i]
+                Some(s) => s.split('[').next(),
+                // v1#branch
+                None => self.name.split('#').next(),
+            },
+        }
+    }
+    /// returns the name of the net a voltage belongs to
+    pub fn net(&self) -> Option<&str> {
+        match self.quantity {
+            OpQuantity::Voltage => Some(&self.name),
+            OpQuantity::Current => None,
+        }
+    }
+}
+
+/// how the rows of the operating point table are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpTableSort {
+    /// voltages then currents, each by name
+    #[default]
+    Name,
+    /// by value, largest first
+    Value,
+    /// by magnitude, largest first
+    Magnitude,
+}
+
+impl OpTableSort {
+    /// all variants, for use in pick lists
+    pub const ALL: [OpTableSort; 3] = [
+        OpTableSort::Name,
+        OpTableSort::Value,
+        OpTableSort::Magnitude,
+    ];
+}
+
+impl Display for OpTableSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OpTableSort::Name => "name",
+                OpTableSort::Value => "value",
+                OpTableSort::Magnitude => "magnitude",
+            }
+        )
+    }
+}
+
+/// node voltages and branch currents of the last dc operating point
+#[derive(Debug, Clone, Default)]
+pub struct OpTable {
+    /// every vector of the operating point
+    rows: Vec<OpRow>,
+    /// rows whose name does not contain the filter (case insensitive) are hidden
+    pub filter: String,
+    /// order of the rows
+    pub sort: OpTableSort,
+}
+
+impl OpTable {
+    /// replaces the rows with the vectors of an operating point, given as name and value
+    pub fn set_vecs<'a>(&mut self, vecs: impl Iterator<Item = (&'a str, f64)>) {
+        self.rows = vecs.map(|(name, value)| OpRow::new(name, value)).collect();
+    }
+    /// removes all rows, e.g. after a failed simulation
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+    /// returns true if there is no operating point
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    /// returns the rows which pass the filter, in order
+    pub fn visible(&self) -> Vec<&OpRow> {
+        let filter = self.filter.to_lowercase();
+        let mut rows: Vec<&OpRow> = self
+            .rows
+            .iter()
+            .filter(|r| r.name.to_lowercase().contains(&filter))
+            .collect();
+        match self.sort {
+            OpTableSort::Name => rows.sort_by(|a, b| {
+                (a.quantity == OpQuantity::Current, &a.name)
+                    .cmp(&(b.quantity == OpQuantity::Current, &b.name))
+            }),
+            OpTableSort::Value => rows.sort_by(|a, b| b.value.total_cmp(&a.value)),
+            OpTableSort::Magnitude => rows.sort_by(|a, b| b.value.abs().total_cmp(&a.value.abs())),
+        }
+        rows
+    }
+    /// returns the visible rows as comma separated values, with a header line
+    pub fn csv(&self) -> String {
+        let mut csv = String::from("name,quantity,value\n");
+        for r in self.visible() {
+            let quantity = match r.quantity {
+                OpQuantity::Voltage => "voltage",
+                OpQuantity::Current => "current",
+            };
+            csv.push_str(&format!("{},{},{:e}\n", r.name, quantity, r.value));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantity_from_name() {
+        assert_eq!(OpRow::new("net_1", 1.0).quantity, OpQuantity::Voltage);
+        assert_eq!(OpRow::new("v1#branch", 1.0).quantity, OpQuantity::Current);
+        assert_eq!(OpRow::new("@r1[i]", 1.0).quantity, OpQuantity::Current);
+    }
+
+    #[test]
+    fn device_of_currents() {
+        assert_eq!(OpRow::new("@r1[i]", 1e-3).device(), Some("r1"));
+        assert_eq!(OpRow::new("@m1[id]", 1e-3).device(), Some("m1"));
+        assert_eq!(OpRow::new("v1#branch", 1e-3).device(), Some("v1"));
+        assert_eq!(OpRow::new("net_1", 1.0).device(), None);
+    }
+
+    #[test]
+    fn net_of_voltages() {
+        assert_eq!(OpRow::new("net_1", 1.0).net(), Some("net_1"));
+        assert_eq!(OpRow::new("@r1[i]", 1e-3).net(), None);
+    }
+
+    #[test]
+    fn filter_and_sort() {
+        let mut table = OpTable::default();
+        table.set_vecs(
+            [("net_2", 2.0), ("v1#branch", -3.0), ("net_1", 1.0)]
+                .iter()
+                .map(|(n, v)| (*n, *v)),
+        );
+        let names =
+            |t: &OpTable| -> Vec<String> { t.visible().iter().map(|r| r.name.clone()).collect() };
+        // voltages before currents
+        assert_eq!(names(&table), ["net_1", "net_2", "v1#branch"]);
+        table.sort = OpTableSort::Magnitude;
+        assert_eq!(names(&table), ["v1#branch", "net_2", "net_1"]);
+        table.filter = String::from("NET");
+        assert_eq!(names(&table), ["net_2", "net_1"]);
+    }
+}
@@ -0,0 +1,254 @@
+//! design parameters - named values emitted as `.param` lines and referenced in device values through `{expressions}`
+//! expressions are evaluated here as well, to validate them and display their value before handing them to ngspice
+
+use crate::schematic::eng;
+use crate::schematic::lexer::Lexer;
+
+/// a named design parameter, e.g. `Rload=10k`
+#[derive(Debug, Clone, Default)]
+pub struct DesignParam {
+    /// parameter name
+    pub name: String,
+    /// parameter value, a number or an expression of previously defined parameters
+    pub expr: String,
+}
+
+/// table of design parameters, in order of definition
+#[derive(Debug, Clone, Default)]
+pub struct DesignParams {
+    params: Vec<DesignParam>,
+}
+
+impl DesignParams {
+    /// returns the parameters in order of definition
+    pub fn params(&self) -> &[DesignParam] {
+        &self.params
+    }
+    /// returns a mut reference to the parameters
+    pub fn params_mut(&mut self) -> &mut Vec<DesignParam> {
+        &mut self.params
+    }
+    /// returns the `.param` lines for the netlist
+    pub fn param_definitions(&self) -> String {
+        self.params
+            .iter()
+            .filter(|p| !p.name.trim().is_empty())
+            .map(|p| format!(".param {}={}\n", p.name.trim(), braced(&p.expr)))
+            .collect()
+    }
+    /// returns the value of every parameter in order of definition. Parameters may only reference those defined before them
+    pub fn evaluate(&self) -> Vec<Result<f64, String>> {
+        let mut scope: Vec<(&str, f64)> = vec![];
+        self.params
+            .iter()
+            .map(|p| {
+                let name = p.name.trim();
+                if !is_identifier(name) {
+                    return Err(format!("invalid name: {}", name));
+                }
+                let value = eval(unbraced(&p.expr), &scope)?;
+                scope.push((name, value));
+                Ok(value)
+            })
+            .collect()
+    }
+    /// returns s with every `{expression}` replaced by its value, or an error describing the first invalid expression.
+    /// Returns None if s holds no expressions
+    pub fn substitute(&self, s: &str) -> Option<Result<String, String>> {
+        if !s.contains('{') {
+            return None;
+        }
+        let values = self.evaluate();
+        let scope: Vec<(&str, f64)> = self
+            .params
+            .iter()
+            .zip(values)
+            .filter_map(|(p, v)| v.ok().map(|v| (p.name.trim(), v)))
+            .collect();
+        let mut ret = String::new();
+        let mut rest = s;
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}') else {
+                return Some(Err(String::from("unmatched {")));
+            };
+            ret.push_str(&rest[..open]);
+            match eval(&rest[open + 1..open + close], &scope) {
+                Ok(v) => ret.push_str(&eng::format(v)),
+                Err(e) => return Some(Err(e)),
+            }
+            rest = &rest[open + close + 1..];
+        }
+        ret.push_str(rest);
+        Some(Ok(ret))
+    }
+}
+
+/// returns expr without enclosing braces
+fn unbraced(expr: &str) -> &str {
+    let expr = expr.trim();
+    expr.strip_prefix('{')
+        .and_then(|e| e.strip_suffix('}'))
+        .unwrap_or(expr)
+}
+
+/// returns expr enclosed in braces if it is not a plain number, as required by ngspice for `.param` expressions
+fn braced(expr: &str) -> String {
+    let expr = unbraced(expr);
+    if eng::parse(expr, None).is_ok() {
+        expr.to_string()
+    } else {
+        format!("{{{}}}", expr)
+    }
+}
+
+/// returns true if s is a valid parameter name
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// evaluates expr, looking up parameter names (case insensitive) in scope
+pub fn eval(expr: &str, scope: &[(&str, f64)]) -> Result<f64, String> {
+    let mut parser = Parser {
+        lex: Lexer::new(expr),
+        scope,
+    };
+    let v = parser.expr()?;
+    parser.lex.skip_ws();
+    if !parser.lex.rest().is_empty() {
+        return Err(format!("unexpected '{}' in {}", parser.lex.rest(), expr));
+    }
+    Ok(v)
+}
+
+/// recursive descent parser of arithmetic expressions
+///
+/// expr := term (('+' | '-') term)*
+/// term := unary (('*' | '/') unary)*
+/// unary := ('+' | '-') unary | power
+/// power := primary (('^' | '**') unary)?
+/// primary := number | name | name '(' expr (',' expr)* ')' | '(' expr ')'
+struct Parser<'a> {
+    lex: Lexer<'a>,
+    scope: &'a [(&'a str, f64)],
+}
+
+impl Parser<'_> {
+    fn expr(&mut self) -> Result<f64, String> {
+        let mut v = self.term()?;
+        loop {
+            if self.lex.eat("+") {
+                v += self.term()?;
+            } else if self.lex.eat("-") {
+                v -= self.term()?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+    fn term(&mut self) -> Result<f64, String> {
+        let mut v = self.unary()?;
+        loop {
+            if self.lex.eat("*") {
+                v *= self.unary()?;
+            } else if self.lex.eat("/") {
+                v /= self.unary()?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.lex.eat("-") {
+            Ok(-self.unary()?)
+        } else if self.lex.eat("+") {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.primary()?;
+        if self.lex.eat("^") || self.lex.eat("**") {
+            Ok(base.powf(self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+    fn primary(&mut self) -> Result<f64, String> {
+        self.lex.skip_ws();
+        if self.lex.eat("(") {
+            let v = self.expr()?;
+            return if self.lex.eat(")") {
+                Ok(v)
+            } else {
+                Err(String::from("expected )"))
+            };
+        }
+        match self.lex.peek() {
+            Some(c) if c.is_ascii_digit() || c == b'.' => self.lex.number(),
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                let name = self
+                    .lex
+                    .take_while(|c| c.is_ascii_alphanumeric() || c == b'_')
+                    .to_string();
+                if self.lex.eat("(") {
+                    let mut args = vec![self.expr()?];
+                    while self.lex.eat(",") {
+                        args.push(self.expr()?);
+                    }
+                    if !self.lex.eat(")") {
+                        return Err(String::from("expected )"));
+                    }
+                    call(&name, &args)
+                } else if name.eq_ignore_ascii_case("pi") {
+                    Ok(std::f64::consts::PI)
+                } else {
+                    self.scope
+                        .iter()
+                        .rev()
+                        .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+                        .map(|(_, v)| *v)
+                        .ok_or_else(|| format!("unknown parameter: {}", name))
+                }
+            }
+            Some(c) => Err(format!("unexpected '{}'", c as char)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+}
+
+/// evaluates function name with args
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let unary = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("{} takes 1 argument", name)),
+    };
+    match name.to_lowercase().as_str() {
+        "sqrt" => unary(f64::sqrt),
+        "exp" => unary(f64::exp),
+        "ln" | "log" => unary(f64::ln),
+        "log10" => unary(f64::log10),
+        "abs" => unary(f64::abs),
+        "sin" => unary(f64::sin),
+        "cos" => unary(f64::cos),
+        "tan" => unary(f64::tan),
+        "atan" => unary(f64::atan),
+        "floor" => unary(f64::floor),
+        "ceil" => unary(f64::ceil),
+        "min" | "max" | "pow" => match args {
+            [a, b] => Ok(match name.to_lowercase().as_str() {
+                "min" => a.min(*b),
+                "max" => a.max(*b),
+                _ => a.powf(*b),
+            }),
+            _ => Err(format!("{} takes 2 arguments", name)),
+        },
+        // statistical functions evaluate to their nominal value
+        "gauss" | "agauss" | "unif" | "aunif" => match args.first() {
+            Some(nom) => Ok(*nom),
+            None => Err(format!("{} takes a nominal value", name)),
+        },
+        _ => Err(format!("unknown function: {}", name)),
+    }
+}
@@ -0,0 +1,250 @@
+//! engineering notation
+//! parsing and formatting of spice numbers such as `4.7k`, `10meg`, `1e-3` or `100nF`
+
+/// spice scale factor suffixes, in descending order
+const SUFFIXES: [(&str, f64); 9] = [
+    ("t", 1e12),
+    ("g", 1e9),
+    ("meg", 1e6),
+    ("k", 1e3),
+    ("", 1.0),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+];
+/// smallest scale factor, formatted separately from SUFFIXES as it can be confused with farad
+const FEMTO: (&str, f64) = ("f", 1e-15);
+
+/// parses a spice number: a mantissa, an optional exponent, an optional scale factor suffix and an optional unit.
+/// If unit is given, it is the only unit accepted after the scale factor (case insensitive), otherwise no unit is accepted.
+/// As in spice, a lone `f` is femto, so `1F` is 1e-15 and one farad is `1`.
+pub fn parse(s: &str, unit: Option<&str>) -> Result<f64, String> {
+    let lower = s.trim().to_lowercase();
+    let bytes = lower.as_bytes();
+    // mantissa
+    let mut i = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        i += 1;
+    }
+    while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+        i += 1;
+    }
+    // exponent
+    if bytes.get(i) == Some(&b'e') {
+        let mut j = i + 1;
+        if matches!(bytes.get(j), Some(b'+' | b'-')) {
+            j += 1;
+        }
+        if bytes.get(j).is_some_and(|c| c.is_ascii_digit()) {
+            while j < bytes.len() && bytes[j].is_ascii_digit() {
+                j += 1;
+            }
+            i = j;
+        }
+    }
+    let num: f64 = lower[..i]
+        .parse()
+        .map_err(|_| format!("not a number: {}", s.trim()))?;
+    // scale factor, longest match first
+    let rest = &lower[i..];
+    let (scale, rest) = SUFFIXES
+        .iter()
+        .chain(std::iter::once(&FEMTO))
+        .filter(|(suffix, _)| !suffix.is_empty() && rest.starts_with(suffix))
+        .max_by_key(|(suffix, _)| suffix.len())
+        .map_or((1.0, rest), |(suffix, scale)| {
+            (*scale, &rest[suffix.len()..])
+        });
+    match unit {
+        _ if rest.is_empty() => Ok(num * scale),
+        Some(u) if rest.eq_ignore_ascii_case(u) => Ok(num * scale),
+        Some(u) => Err(format!("invalid suffix '{}', expected {}", rest, u)),
+        None => Err(format!("invalid suffix '{}'", rest)),
+    }
+}
+
+/// formats v in engineering notation with a spice scale factor suffix and up to 4 decimals, e.g. `15.9155k`
+pub fn format(v: f64) -> String {
+    if v == 0.0 || !v.is_finite() {
+        return format!("{}", v);
+    }
+    let mag = v.abs();
+    if !(FEMTO.1..1e15).contains(&mag) {
+        return format!("{:e}", v);
+    }
+    let (suffix, scale) = SUFFIXES
+        .iter()
+        .chain(std::iter::once(&FEMTO))
+        // pick the scale after rounding, so that 999.99999 becomes 1k rather than 1000
+        .find(|(_, scale)| round4(mag / scale) >= 1.0)
+        .copied()
+        .unwrap_or(FEMTO);
+    let mantissa = format!("{:.4}", v / scale);
+    let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", mantissa, suffix)
+}
+
+/// formats v in engineering notation with a spice scale factor suffix and every significant digit, e.g. `15.915494309189533k`,
+/// so that the value is written to the netlist unrounded
+pub fn format_exact(v: f64) -> String {
+    if v == 0.0 || !v.is_finite() {
+        return format!("{}", v);
+    }
+    // shortest representation parsing back to v, e.g. `-1.5915494309189533e4`
+    let sci = format!("{:e}", v);
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let eng_exp = exp.div_euclid(3) * 3;
+    let suffix = match eng_exp {
+        -15 => FEMTO.0,
+        -12..=12 => SUFFIXES[((12 - eng_exp) / 3) as usize].0,
+        _ => return sci,
+    };
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let mut digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    // the decimal point moves right by the difference of the exponents
+    let point = 1 + (exp - eng_exp) as usize;
+    while digits.len() < point {
+        digits.push('0');
+    }
+    let (int, frac) = digits.split_at(point);
+    if frac.is_empty() {
+        format!("{}{}{}", sign, int, suffix)
+    } else {
+        format!("{}{}.{}{}", sign, int, frac, suffix)
+    }
+}
+
+/// formats v as `format_exact` does followed by unit, e.g. `4.7kohm`.
+/// The unit is left out where it would be read as a scale factor, e.g. one farad is `1` rather than `1F`
+pub fn format_unit(v: f64, unit: &str) -> String {
+    let s = format_exact(v);
+    let with_unit = format!("{}{}", s, unit);
+    if parse(&with_unit, Some(unit)) == parse(&s, None) {
+        with_unit
+    } else {
+        s
+    }
+}
+
+/// rounds v to 4 decimals
+fn round4(v: f64) -> f64 {
+    (v * 1e4).round() / 1e4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// asserts that a and b agree to within a relative tolerance
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-12 * b.abs(), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn parse_suffixes() {
+        assert_close(parse("4.7k", None).unwrap(), 4.7e3);
+        assert_close(parse("2T", None).unwrap(), 2e12);
+        assert_close(parse("3g", None).unwrap(), 3e9);
+        assert_close(parse("10u", None).unwrap(), 10e-6);
+        assert_close(parse("22n", None).unwrap(), 22e-9);
+        assert_close(parse("-5p", None).unwrap(), -5e-12);
+        assert_close(parse(" 1.5 ", None).unwrap(), 1.5);
+    }
+
+    #[test]
+    fn parse_meg_and_milli() {
+        assert_close(parse("10meg", None).unwrap(), 10e6);
+        assert_close(parse("10MEG", None).unwrap(), 10e6);
+        assert_close(parse("10m", None).unwrap(), 10e-3);
+        assert_close(parse("10M", None).unwrap(), 10e-3);
+    }
+
+    #[test]
+    fn parse_lone_f_is_femto() {
+        assert_close(parse("1F", Some("F")).unwrap(), 1e-15);
+        assert_close(parse("1f", None).unwrap(), 1e-15);
+        assert_close(parse("100nF", Some("F")).unwrap(), 100e-9);
+        assert_close(parse("1", Some("F")).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn parse_units() {
+        assert_close(parse("1kohm", Some("ohm")).unwrap(), 1e3);
+        assert_close(parse("1KOhm", Some("ohm")).unwrap(), 1e3);
+        assert_close(parse("10uH", Some("H")).unwrap(), 10e-6);
+        assert!(parse("1kohm", None).is_err());
+        assert!(parse("1kH", Some("ohm")).is_err());
+    }
+
+    #[test]
+    fn parse_exponents() {
+        assert_close(parse("1e3", None).unwrap(), 1e3);
+        assert_close(parse("2.5E+2", None).unwrap(), 250.0);
+        assert_close(parse("1e-3k", None).unwrap(), 1.0);
+        assert_close(parse(".5e1", None).unwrap(), 5.0);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse("", None).is_err());
+        assert!(parse("abc", None).is_err());
+        assert!(parse("k", None).is_err());
+        assert!(parse("1x", None).is_err());
+        assert!(parse("1.2.3", None).is_err());
+    }
+
+    #[test]
+    fn format_suffixes() {
+        assert_eq!(format(0.0), "0");
+        assert_eq!(format(1.0), "1");
+        assert_eq!(format(4700.0), "4.7k");
+        assert_eq!(format(10e6), "10meg");
+        assert_eq!(format(10e-3), "10m");
+        assert_eq!(format(-22e-9), "-22n");
+        assert_eq!(format(1e-15), "1f");
+        assert_eq!(format(1e15), "1e15");
+    }
+
+    #[test]
+    fn format_rounds_to_4_decimals() {
+        assert_eq!(format(15915.494), "15.9155k");
+        assert_eq!(format(999.99999), "1k");
+    }
+
+    #[test]
+    fn format_parses_back() {
+        for v in [1.0, 4.7e3, 10e6, 33e-12, 2.2e-15, 1.5e12] {
+            assert_close(parse(&format(v), None).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn format_exact_keeps_every_digit() {
+        assert_eq!(format_exact(0.0), "0");
+        assert_eq!(format_exact(4700.0), "4.7k");
+        assert_eq!(format_exact(15915.494309189533), "15.915494309189533k");
+        assert_eq!(format_exact(100e3), "100k");
+        assert_eq!(format_exact(-22e-9), "-22n");
+        assert_eq!(format_exact(1e-15), "1f");
+        assert_eq!(format_exact(1e15), "1e15");
+        for v in [1.0, 4.7e3, 10e6, 33e-12, 2.2e-15, 1.5e12, 1.23456789e-7] {
+            assert_close(parse(&format_exact(v), None).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn format_with_unit() {
+        assert_eq!(format_unit(4.7e3, "ohm"), "4.7kohm");
+        assert_eq!(format_unit(100e-9, "F"), "100nF");
+        assert_eq!(format_unit(10e-6, "H"), "10uH");
+        assert_eq!(format_unit(1e-15, "F"), "1fF");
+        // a lone F is femto
+        assert_eq!(format_unit(1.0, "F"), "1");
+        assert_eq!(format_unit(1e-18, "F"), "1e-18");
+    }
+}
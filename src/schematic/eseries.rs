@@ -0,0 +1,93 @@
+//! standard values (E-series) of passive components, IEC 60063
+
+use std::fmt::Display;
+
+/// E12 mantissas
+const E12: [f64; 12] = [1.0, 1.2, 1.5, 1.8, 2.2, 2.7, 3.3, 3.9, 4.7, 5.6, 6.8, 8.2];
+/// E24 mantissas
+const E24: [f64; 24] = [
+    1.0, 1.1, 1.2, 1.3, 1.5, 1.6, 1.8, 2.0, 2.2, 2.4, 2.7, 3.0, 3.3, 3.6, 3.9, 4.3, 4.7, 5.1, 5.6,
+    6.2, 6.8, 7.5, 8.2, 9.1,
+];
+
+/// standard value series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ESeries {
+    /// 12 values per decade, 10% tolerance
+    E12,
+    /// 24 values per decade, 5% tolerance
+    #[default]
+    E24,
+    /// 48 values per decade, 2% tolerance
+    E48,
+    /// 96 values per decade, 1% tolerance
+    E96,
+}
+
+impl ESeries {
+    /// all variants, for use in pick lists
+    pub const ALL: [ESeries; 4] = [ESeries::E12, ESeries::E24, ESeries::E48, ESeries::E96];
+
+    /// returns the mantissas of the series, in [1, 10)
+    fn mantissas(self) -> Vec<f64> {
+        match self {
+            ESeries::E12 => E12.to_vec(),
+            ESeries::E24 => E24.to_vec(),
+            // E48 and E96 follow the geometric series rounded to 3 significant digits
+            ESeries::E48 | ESeries::E96 => {
+                let n = if self == ESeries::E48 { 48 } else { 96 };
+                (0..n)
+                    .map(|i| (10f64.powf(i as f64 / n as f64) * 100.0).round() / 100.0)
+                    .collect()
+            }
+        }
+    }
+    /// returns the standard values nearest below and above v, which are equal if v is a standard value.
+    /// Returns None if v is not positive
+    pub fn neighbours(self, v: f64) -> Option<(f64, f64)> {
+        if !(v > 0.0 && v.is_finite()) {
+            return None;
+        }
+        let decade = v.log10().floor() as i32;
+        let candidates: Vec<f64> = (decade - 1..=decade + 1)
+            .flat_map(|d| self.mantissas().into_iter().map(move |m| m * 10f64.powi(d)))
+            .collect();
+        // tolerate rounding errors of values parsed from text
+        let below = candidates
+            .iter()
+            .copied()
+            .filter(|c| *c <= v * (1.0 + 1e-9))
+            .fold(f64::NAN, f64::max);
+        let above = candidates
+            .iter()
+            .copied()
+            .filter(|c| *c >= v * (1.0 - 1e-9))
+            .fold(f64::NAN, f64::min);
+        Some((below, above))
+    }
+    /// returns the standard value nearest to v on a logarithmic scale. Returns None if v is not positive
+    pub fn nearest(self, v: f64) -> Option<f64> {
+        self.neighbours(v).map(|(below, above)| {
+            if (v / below).ln() <= (above / v).ln() {
+                below
+            } else {
+                above
+            }
+        })
+    }
+}
+
+impl Display for ESeries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ESeries::E12 => "E12",
+                ESeries::E24 => "E24",
+                ESeries::E48 => "E48",
+                ESeries::E96 => "E96",
+            }
+        )
+    }
+}
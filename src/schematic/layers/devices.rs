@@ -4,6 +4,8 @@ use std::collections::HashSet;
 
 use crate::schematic::atoms::DeviceClass;
 use crate::schematic::atoms::RcRDevice;
+use crate::schematic::eng;
+use crate::schematic::eseries::ESeries;
 use crate::schematic::interactable::Interactive;
 use crate::transforms::{self, SSPoint, VCTransform, VSBox, VSPoint};
 use crate::Drawable;
@@ -203,6 +205,12 @@ impl Devices {
             d.0.borrow_mut().op(pkvecvaluesall);
         }
     }
+    /// clears the operating point of every device
+    pub fn clear_op(&mut self) {
+        for d in &self.set {
+            d.0.borrow_mut().clear_op();
+        }
+    }
     /// inserts device d into self.
     pub fn insert(&mut self, d: RcRDevice) {
         if !self.set.contains(&d) {
@@ -305,12 +313,51 @@ impl Devices {
         }
         false
     }
+    /// returns the ngspice vector of the current into the device terminal at ssp, the signal name and the sign the vector takes
+    pub fn terminal_current_at(&self, ssp: SSPoint) -> Option<(String, String, f64)> {
+        self.set.iter().find_map(|d| {
+            let d = d.0.borrow();
+            let port = d.port_at(ssp)?;
+            let (vec, terminal, sign) = d.class().terminal_current(&d.ng_id(), port)?;
+            Some((vec, format!("i({}.{})", d.ng_id(), terminal), sign))
+        })
+    }
+    /// returns the signal name and operating point current of the device terminal at ssp
+    pub fn op_current_at(&self, ssp: SSPoint) -> Option<(String, f64)> {
+        self.set.iter().find_map(|d| {
+            let d = d.0.borrow();
+            let port = d.port_at(ssp)?;
+            let i = d.op_current_at_port(port)?;
+            let (_, terminal, _) = d.class().terminal_current(&d.ng_id(), port)?;
+            Some((format!("i({}.{})", d.ng_id(), terminal), i))
+        })
+    }
     pub fn delete_item(&mut self, d: &RcRDevice) {
         self.set.remove(d);
     }
     pub fn get_set(&self) -> &HashSet<RcRDevice> {
         &self.set
     }
+    /// rounds the value of every resistor and capacitor to the nearest standard value of series.
+    /// Returns the id, old and new value of every changed device. Devices with raw parameters are left as they are
+    pub fn snap_to_series(&self, series: ESeries) -> Vec<(String, f64, f64)> {
+        let mut changes = vec![];
+        for d in &self.set {
+            let mut d = d.0.borrow_mut();
+            let summary = d.class().param_summary();
+            let Some(old) = d.class().passive_value(&summary) else {
+                continue;
+            };
+            if let Some(new) = series.nearest(old) {
+                if eng::format(new) != eng::format(old) {
+                    d.class_mut().set_raw_param(eng::format(new));
+                    changes.push((d.ng_id(), old, new));
+                }
+            }
+        }
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+    }
 }
 
 impl Drawable for RcRDevice {
@@ -3,6 +3,10 @@ pub use devices::Devices;
 mod net_labels;
 use enum_dispatch::enum_dispatch;
 pub use net_labels::NetLabels;
+mod net_markers;
+pub use net_markers::NetMarkers;
+mod probes;
+pub use probes::Probes;
 mod nets;
 pub use nets::Nets;
 
@@ -13,8 +17,10 @@ use iced::widget::canvas::Frame;
 
 pub use self::devices::DevicesLayer;
 pub use self::net_labels::NetLabelsLayer;
+pub use self::net_markers::NetMarkersLayer;
 pub use self::nets::DijkstraSt;
 pub use self::nets::NetsLayer;
+pub use self::probes::ProbesLayer;
 
 use super::atoms::SchematicAtom;
 
@@ -33,6 +39,8 @@ pub enum SchematicLayerEnum {
     // Ports,
     DevicesLayer,
     NetLabelsLayer,
+    NetMarkersLayer,
+    ProbesLayer,
     // LineSegs,
     // CirArcs,
     // Bounds,
@@ -0,0 +1,160 @@
+//! net markers
+//! initial conditions and nodesets placed on nets
+
+use std::collections::HashSet;
+
+use crate::transforms::{SSPoint, VCTransform, VSBox, VSPoint};
+use crate::Drawable;
+use iced::widget::canvas::Frame;
+
+use crate::schematic::atoms::{MarkerKind, NetMarker, RcRMarker};
+
+use super::{Nets, SchematicLayerTrait};
+
+pub type NetMarkersLayer = Box<NetMarkers>;
+
+impl SchematicLayerTrait<RcRMarker> for NetMarkersLayer {
+    #[doc = " draws self\\'s contents on frame"]
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        for m in &self.set {
+            m.0.borrow().draw_persistent(vct, vcscale, frame);
+        }
+    }
+
+    #[doc = " returns bounding box containing all atoms in layer"]
+    fn bounds(&self) -> VSBox {
+        self.bounding_box()
+    }
+
+    #[doc = " increments count for every atom over vsp, returns Some(atom) once count == skip"]
+    fn selectable(&self, vsp: VSPoint, skip: usize, count: &mut usize) -> Option<RcRMarker> {
+        for m in &self.set {
+            if m.0.borrow().interactable.contains_vsp(vsp) {
+                if *count == skip {
+                    // skipped just enough
+                    return Some(m.clone());
+                } else {
+                    *count += 1;
+                }
+            }
+        }
+        None
+    }
+
+    #[doc = " returns slice of all atoms in layer which intersect with closed area defined by vsb"]
+    fn intersect(&self, vsb: &VSBox) -> Box<[RcRMarker]> {
+        self.set
+            .iter()
+            .filter(|m| m.0.borrow().interactable.intersects_vsb(vsb))
+            .cloned()
+            .collect()
+    }
+
+    #[doc = " returns slice of all atoms in layer which fit in open area defined by vsb"]
+    fn contained(&self, vsb: &VSBox) -> Box<[RcRMarker]> {
+        self.contained_by(vsb).into()
+    }
+
+    #[doc = " place the device in layer - replace existing if atom equates to existing, or adds new if not"]
+    fn place(&mut self, atom: RcRMarker) {
+        self.set.insert(atom);
+    }
+
+    #[doc = " delete the specified atom if it exists"]
+    fn delete(&mut self, atom: &RcRMarker) {
+        self.set.remove(atom);
+    }
+}
+
+/// struct containing all net markers in schematic
+#[derive(Debug, Default, Clone)]
+pub struct NetMarkers {
+    /// set of all markers
+    set: HashSet<RcRMarker>,
+}
+
+impl NetMarkers {
+    /// returns the first marker after skip which intersects with curpos_vsp, if any.
+    /// count is updated to track the number of elements skipped over
+    pub fn selectable(
+        &mut self,
+        curpos_vsp: VSPoint,
+        skip: usize,
+        count: &mut usize,
+    ) -> Option<RcRMarker> {
+        for m in &self.set {
+            if m.0.borrow().interactable.contains_vsp(curpos_vsp) {
+                if *count == skip {
+                    // skipped just enough
+                    return Some(m.clone());
+                } else {
+                    *count += 1;
+                }
+            }
+        }
+        None
+    }
+    /// returns the bounding box of all markers
+    pub fn bounding_box(&self) -> VSBox {
+        let pts = self.set.iter().flat_map(|m| {
+            [
+                m.0.borrow().interactable.bounds.min,
+                m.0.borrow().interactable.bounds.max,
+            ]
+            .into_iter()
+        });
+        VSBox::from_points(pts)
+    }
+    /// inserts marker m into self.
+    pub fn insert(&mut self, m: RcRMarker) {
+        self.set.insert(m);
+    }
+    /// return vector of RcRMarker which are contained by vsb
+    pub fn contained_by(&self, vsb: &VSBox) -> Vec<RcRMarker> {
+        self.set
+            .iter()
+            .filter(|m| m.0.borrow().interactable.contained_by(vsb))
+            .cloned()
+            .collect()
+    }
+    pub fn delete_item(&mut self, m: &RcRMarker) {
+        self.set.remove(m);
+    }
+    pub fn new_marker(kind: MarkerKind) -> RcRMarker {
+        RcRMarker(std::rc::Rc::new(std::cell::RefCell::new(NetMarker::new(
+            kind,
+        ))))
+    }
+    /// returns true if any marker is on ssp
+    pub fn any_occupy_ssp(&self, ssp: SSPoint) -> bool {
+        self.set.iter().any(|m| m.0.borrow().pos() == ssp)
+    }
+    /// returns the `.ic` and `.nodeset` lines of the markers. Markers not placed on a net are left out
+    pub fn spice_lines(&self, nets: &Nets) -> String {
+        let mut lines: Vec<String> = self
+            .set
+            .iter()
+            .filter_map(|m| {
+                let m = m.0.borrow();
+                nets.net_name_at(m.pos()).map(|net| m.spice_line(&net))
+            })
+            .collect();
+        // set iteration order is arbitrary, keep the netlist stable
+        lines.sort();
+        lines.concat()
+    }
+}
+
+impl Drawable for NetMarkers {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        for m in &self.set {
+            m.0.borrow().draw_persistent(vct, vcscale, frame);
+        }
+    }
+    fn draw_selected(&self, _vct: VCTransform, _vcscale: f32, _frame: &mut Frame) {
+        panic!("not intended for use");
+    }
+    fn draw_preview(&self, _vct: VCTransform, _vcscale: f32, _frame: &mut Frame) {
+        panic!("not intended for use");
+    }
+}
@@ -0,0 +1,201 @@
+//! probes
+//! voltage and current probes placed on nets and device terminals
+
+use std::collections::HashSet;
+
+use crate::transforms::{SSPoint, VCTransform, VSBox, VSPoint};
+use crate::Drawable;
+use iced::widget::canvas::Frame;
+
+use crate::schematic::atoms::{Probe, ProbeKind, ProbeSignal, RcRProbe};
+
+use super::{Devices, Nets, SchematicLayerTrait};
+
+pub type ProbesLayer = Box<Probes>;
+
+impl SchematicLayerTrait<RcRProbe> for ProbesLayer {
+    #[doc = " draws self\\'s contents on frame"]
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        for p in &self.set {
+            p.0.borrow().draw_persistent(vct, vcscale, frame);
+        }
+    }
+
+    #[doc = " returns bounding box containing all atoms in layer"]
+    fn bounds(&self) -> VSBox {
+        self.bounding_box()
+    }
+
+    #[doc = " increments count for every atom over vsp, returns Some(atom) once count == skip"]
+    fn selectable(&self, vsp: VSPoint, skip: usize, count: &mut usize) -> Option<RcRProbe> {
+        for p in &self.set {
+            if p.0.borrow().interactable.contains_vsp(vsp) {
+                if *count == skip {
+                    // skipped just enough
+                    return Some(p.clone());
+                } else {
+                    *count += 1;
+                }
+            }
+        }
+        None
+    }
+
+    #[doc = " returns slice of all atoms in layer which intersect with closed area defined by vsb"]
+    fn intersect(&self, vsb: &VSBox) -> Box<[RcRProbe]> {
+        self.set
+            .iter()
+            .filter(|p| p.0.borrow().interactable.intersects_vsb(vsb))
+            .cloned()
+            .collect()
+    }
+
+    #[doc = " returns slice of all atoms in layer which fit in open area defined by vsb"]
+    fn contained(&self, vsb: &VSBox) -> Box<[RcRProbe]> {
+        self.contained_by(vsb).into()
+    }
+
+    #[doc = " place the device in layer - replace existing if atom equates to existing, or adds new if not"]
+    fn place(&mut self, atom: RcRProbe) {
+        self.set.insert(atom);
+    }
+
+    #[doc = " delete the specified atom if it exists"]
+    fn delete(&mut self, atom: &RcRProbe) {
+        self.set.remove(atom);
+    }
+}
+
+/// struct containing all probes in schematic
+#[derive(Debug, Default, Clone)]
+pub struct Probes {
+    /// set of all probes
+    set: HashSet<RcRProbe>,
+}
+
+impl Probes {
+    /// returns the first probe after skip which intersects with curpos_vsp, if any.
+    /// count is updated to track the number of elements skipped over
+    pub fn selectable(
+        &mut self,
+        curpos_vsp: VSPoint,
+        skip: usize,
+        count: &mut usize,
+    ) -> Option<RcRProbe> {
+        for p in &self.set {
+            if p.0.borrow().interactable.contains_vsp(curpos_vsp) {
+                if *count == skip {
+                    // skipped just enough
+                    return Some(p.clone());
+                } else {
+                    *count += 1;
+                }
+            }
+        }
+        None
+    }
+    /// returns the bounding box of all probes
+    pub fn bounding_box(&self) -> VSBox {
+        let pts = self.set.iter().flat_map(|p| {
+            [
+                p.0.borrow().interactable.bounds.min,
+                p.0.borrow().interactable.bounds.max,
+            ]
+            .into_iter()
+        });
+        VSBox::from_points(pts)
+    }
+    /// inserts probe p into self.
+    pub fn insert(&mut self, p: RcRProbe) {
+        self.set.insert(p);
+    }
+    /// return vector of RcRProbe which are contained by vsb
+    pub fn contained_by(&self, vsb: &VSBox) -> Vec<RcRProbe> {
+        self.set
+            .iter()
+            .filter(|p| p.0.borrow().interactable.contained_by(vsb))
+            .cloned()
+            .collect()
+    }
+    pub fn delete_item(&mut self, p: &RcRProbe) {
+        self.set.remove(p);
+    }
+    pub fn new_probe(kind: ProbeKind) -> RcRProbe {
+        RcRProbe(std::rc::Rc::new(std::cell::RefCell::new(Probe::new(kind))))
+    }
+    /// returns true if any probe tip is on ssp
+    pub fn any_occupy_ssp(&self, ssp: SSPoint) -> bool {
+        self.set.iter().any(|p| p.0.borrow().tips().contains(&ssp))
+    }
+    /// resolves the signal of every probe against nets and devices, sorted by name. Probes not placed on a net or terminal,
+    /// and voltage probes on ground, are left out
+    pub fn signals(&self, nets: &Nets, devices: &Devices) -> Vec<ProbeSignal> {
+        // ground is not a vector
+        let net_vec = |ssp: SSPoint| {
+            nets.net_name_at(ssp).map(|n| {
+                let is_vec = n != "0";
+                (n, is_vec)
+            })
+        };
+        let mut signals = vec![];
+        for p in &self.set {
+            let mut p = p.0.borrow_mut();
+            let tips = p.tips();
+            let signal = match p.kind() {
+                ProbeKind::Voltage => {
+                    net_vec(tips[0])
+                        .filter(|(_, is_vec)| *is_vec)
+                        .map(|(net, _)| ProbeSignal {
+                            name: format!("v({})", net),
+                            vec: net,
+                            ref_vec: None,
+                            sign: 1.0,
+                        })
+                }
+                ProbeKind::Differential => match (net_vec(tips[0]), net_vec(tips[1])) {
+                    (Some((pos, true)), Some((neg, neg_is_vec))) => Some(ProbeSignal {
+                        name: format!("v({},{})", pos, neg),
+                        vec: pos,
+                        ref_vec: neg_is_vec.then_some(neg),
+                        sign: 1.0,
+                    }),
+                    (Some((pos, false)), Some((neg, true))) => Some(ProbeSignal {
+                        name: format!("v({},{})", pos, neg),
+                        vec: neg,
+                        ref_vec: None,
+                        sign: -1.0,
+                    }),
+                    _ => None,
+                },
+                ProbeKind::Current => {
+                    devices
+                        .terminal_current_at(tips[0])
+                        .map(|(vec, name, sign)| ProbeSignal {
+                            name,
+                            vec,
+                            ref_vec: None,
+                            sign,
+                        })
+                }
+            };
+            p.set_signal_name(signal.as_ref().map(|s| s.name.clone()));
+            signals.extend(signal);
+        }
+        signals.sort_by(|a, b| a.name.cmp(&b.name));
+        signals
+    }
+}
+
+impl Drawable for Probes {
+    fn draw_persistent(&self, vct: VCTransform, vcscale: f32, frame: &mut Frame) {
+        for p in &self.set {
+            p.0.borrow().draw_persistent(vct, vcscale, frame);
+        }
+    }
+    fn draw_selected(&self, _vct: VCTransform, _vcscale: f32, _frame: &mut Frame) {
+        panic!("not intended for use");
+    }
+    fn draw_preview(&self, _vct: VCTransform, _vcscale: f32, _frame: &mut Frame) {
+        panic!("not intended for use");
+    }
+}
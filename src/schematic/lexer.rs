@@ -0,0 +1,70 @@
+//! lexer
+//! scanning of whitespace, symbols, names and spice numbers, shared by the expression parsers of
+//! design parameters and waveform math
+
+use crate::schematic::eng;
+
+/// cursor over the bytes of an expression
+pub struct Lexer<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Lexer {
+            s: s.as_bytes(),
+            pos: 0,
+        }
+    }
+    pub fn skip_ws(&mut self) {
+        while self.pos < self.s.len() && self.s[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+    /// skips whitespace and consumes token if it is next, ignoring case
+    pub fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        if self.s[self.pos..]
+            .get(..token.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(token.as_bytes()))
+        {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    /// consumes and returns the longest run of bytes satisfying f
+    pub fn take_while(&mut self, f: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.pos < self.s.len() && f(self.s[self.pos]) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.pos]).unwrap_or_default()
+    }
+    /// returns the next byte without consuming it
+    pub fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+    /// returns the text not consumed yet
+    pub fn rest(&self) -> &'a str {
+        std::str::from_utf8(&self.s[self.pos..]).unwrap_or_default()
+    }
+    /// consumes a spice number, e.g. `4.7k`, `1e-3` or `100nF`. The next byte should be a digit or `.`
+    pub fn number(&mut self) -> Result<f64, String> {
+        let mut num = self
+            .take_while(|c| c.is_ascii_digit() || c == b'.')
+            .to_string();
+        // exponent
+        if matches!(self.s.get(self.pos), Some(b'e' | b'E'))
+            && matches!(self.s.get(self.pos + 1), Some(c) if c.is_ascii_digit() || *c == b'-' || *c == b'+')
+        {
+            self.pos += 2;
+            num.push_str(std::str::from_utf8(&self.s[self.pos - 2..self.pos]).unwrap_or_default());
+            num.push_str(self.take_while(|c| c.is_ascii_digit()));
+        }
+        num.push_str(self.take_while(|c| c.is_ascii_alphabetic()));
+        eng::parse(&num, None)
+    }
+}
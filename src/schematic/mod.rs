@@ -3,9 +3,14 @@
 
 mod atoms;
 pub mod circuit;
+mod design_params;
+pub mod eng;
+mod eseries;
 mod interactable;
 mod layers;
+pub mod lexer;
 mod models;
+mod sim_options;
 pub mod symbols;
 mod viewport;
 
@@ -522,6 +527,22 @@ where
     C: Content<E, M>,
     E: SchematicAtom,
 {
+    /// returns the set of selected elements
+    pub fn selected(&self) -> &HashSet<E> {
+        &self.selected
+    }
+    /// make e the only selected element
+    pub fn select(&mut self, e: E) {
+        self.selected.clear();
+        self.selected.insert(e.clone());
+        self.active_element = Some(e);
+    }
+    /// make es the selected elements, with no active element
+    pub fn select_all(&mut self, es: impl IntoIterator<Item = E>) {
+        self.selected.clear();
+        self.selected.extend(es);
+        self.active_element = None;
+    }
     /// update schematic cursor position
     fn update_cursor_vsp(&mut self, curpos_vsp: VSPoint) {
         self.curpos_vsp = curpos_vsp;
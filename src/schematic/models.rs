@@ -2,9 +2,13 @@
 //!
 //!
 
+use crate::schematic::atoms::Variation;
+
 #[derive(Debug, Clone)]
 pub struct NgModels {
     models: Vec<NgModel>,
+    /// model parameters varied in monte carlo and corner analyses
+    variations: Vec<ModelVariation>,
 }
 
 impl Default for NgModels {
@@ -33,18 +37,75 @@ impl Default for NgModels {
                     definition: String::from("NPN"),
                 },
             ],
+            variations: vec![],
         }
     }
 }
 
 impl NgModels {
-    pub fn model_definitions(&self) -> String {
+    /// returns the model definitions for the netlist, with parameter variations applied according to variation.
+    /// idx is the index of the next varied value in the netlist
+    pub fn model_definitions(&self, variation: &Variation, idx: &mut usize) -> String {
         let mut ret = String::new();
         for m in &self.models {
-            ret.push_str(&m.model_line())
+            let mut params = String::new();
+            for v in self
+                .variations
+                .iter()
+                .filter(|v| v.model.eq_ignore_ascii_case(&m.name))
+            {
+                params.push_str(&format!(
+                    " {}={}",
+                    v.param,
+                    variation.apply(&v.nominal, v.tol, *idx)
+                ));
+                *idx += 1;
+            }
+            ret.push_str(&m.model_line(&params))
         }
         ret
     }
+    /// returns the number of varied model parameters
+    pub fn variations_count(&self) -> usize {
+        self.variations
+            .iter()
+            .filter(|v| {
+                self.models
+                    .iter()
+                    .any(|m| m.name.eq_ignore_ascii_case(&v.model))
+            })
+            .count()
+    }
+    /// sets the model parameter variations from a string of `;` separated entries in the form `MODEL param=nominal tol%`,
+    /// e.g. `MOSN vto=0.7 10; MOSP vto=-0.7 10`. Returns an error describing the first malformed entry.
+    pub fn set_variations(&mut self, s: &str) -> Result<(), String> {
+        let mut variations = vec![];
+        for entry in s.split(';').filter(|e| !e.trim().is_empty()) {
+            let tokens: Vec<&str> = entry.split_whitespace().collect();
+            let parsed = match tokens[..] {
+                [model, assignment, tol] => {
+                    assignment.split_once('=').and_then(|(param, nominal)| {
+                        tol.trim_end_matches('%')
+                            .parse::<f32>()
+                            .ok()
+                            .map(|tol| ModelVariation {
+                                model: model.to_string(),
+                                param: param.to_string(),
+                                nominal: nominal.to_string(),
+                                tol: tol / 100.0,
+                            })
+                    })
+                }
+                _ => None,
+            };
+            match parsed {
+                Some(v) => variations.push(v),
+                None => return Err(format!("malformed model variation: {}", entry.trim())),
+            }
+        }
+        self.variations = variations;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,7 +115,21 @@ struct NgModel {
 }
 
 impl NgModel {
-    fn model_line(&self) -> String {
-        format!(".model {} {}\n", self.name, self.definition)
+    /// returns the model line, with extra_params appended to the definition
+    fn model_line(&self, extra_params: &str) -> String {
+        format!(".model {} {}{}\n", self.name, self.definition, extra_params)
     }
 }
+
+/// variation of a single model parameter
+#[derive(Debug, Clone)]
+struct ModelVariation {
+    /// name of the varied model
+    model: String,
+    /// name of the varied parameter
+    param: String,
+    /// nominal value of the parameter
+    nominal: String,
+    /// relative tolerance of the parameter
+    tol: f32,
+}
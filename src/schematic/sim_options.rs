@@ -0,0 +1,177 @@
+//! simulation options - emitted as `.options`, `.temp` and `.nodeset` lines
+
+use std::fmt::Display;
+
+use crate::schematic::eng;
+
+/// analyses which may override the simulation options
+pub const OVERRIDE_ANALYSES: [&str; 8] = ["op", "tran", "ac", "dc", "noise", "pz", "tf", "sens"];
+
+/// integration method of transient analyses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Method {
+    /// trapezoidal, the ngspice default
+    #[default]
+    Trap,
+    /// gear, more robust against ringing
+    Gear,
+}
+
+impl Method {
+    /// all variants, for use in pick lists
+    pub const ALL: [Method; 2] = [Method::Trap, Method::Gear];
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Method::Trap => "trap",
+                Method::Gear => "gear",
+            }
+        )
+    }
+}
+
+/// simulation options of the schematic
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimOptions {
+    /// `.options` entries as name and value, in order of definition. Flags such as `noopiter` have an empty value
+    options: Vec<(String, String)>,
+    /// initial guesses of net voltages for the operating point, as net name and voltage
+    nodesets: Vec<(String, f64)>,
+    /// circuit temperature (degC), the ngspice default if empty
+    pub temp: String,
+    /// options applied to a single analysis, as analysis name (e.g. `tran`) and `name=value` entries
+    overrides: Vec<(String, String)>,
+    /// temperatures (degC) of the temperature sweep, separated by spaces
+    pub temp_sweep: String,
+}
+
+impl SimOptions {
+    /// returns the value of option name, if set
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+    /// sets option name to value, replacing its previous value. An empty value sets a flag
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self
+            .options
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some(o) => o.1 = value.to_string(),
+            None => self.options.push((name.to_string(), value.to_string())),
+        }
+    }
+    /// removes option name
+    pub fn unset(&mut self, name: &str) {
+        self.options.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    }
+    /// returns the integration method
+    pub fn method(&self) -> Method {
+        match self.get("method") {
+            Some(m) if m.eq_ignore_ascii_case("gear") => Method::Gear,
+            _ => Method::Trap,
+        }
+    }
+    /// sets the integration method, leaving the option out for the default
+    pub fn set_method(&mut self, method: Method) {
+        match method {
+            Method::Trap => self.unset("method"),
+            Method::Gear => self.set("method", "gear"),
+        }
+    }
+    /// returns the options overriding those of the schematic for analysis, empty if none
+    pub fn override_for(&self, analysis: &str) -> &str {
+        self.overrides
+            .iter()
+            .find(|(a, _)| a == analysis)
+            .map(|(_, o)| o.as_str())
+            .unwrap_or_default()
+    }
+    /// sets the options overriding those of the schematic for analysis. Empty options remove the override
+    pub fn set_override(&mut self, analysis: &str, options: String) {
+        self.overrides.retain(|(a, _)| a != analysis);
+        if !options.trim().is_empty() {
+            self.overrides.push((analysis.to_string(), options));
+        }
+    }
+    /// returns the ngspice command setting the override options of the analysis run by cmd, if any
+    pub fn override_command(&self, cmd: &str) -> Option<String> {
+        let analysis = cmd.split_whitespace().next()?;
+        let options = self.override_for(analysis).trim();
+        (!options.is_empty()).then(|| format!("option {}", options))
+    }
+    /// returns the temperatures of the temperature sweep
+    pub fn temps(&self) -> Result<Vec<f64>, String> {
+        let temps = self
+            .temp_sweep
+            .split_whitespace()
+            .map(|t| eng::parse(t, None))
+            .collect::<Result<Vec<f64>, String>>()?;
+        if temps.is_empty() {
+            return Err(String::from("no temperatures to sweep"));
+        }
+        Ok(temps)
+    }
+    /// returns the net voltage guesses
+    pub fn nodesets(&self) -> &[(String, f64)] {
+        &self.nodesets
+    }
+    /// replaces the net voltage guesses
+    pub fn set_nodesets(&mut self, nodesets: Vec<(String, f64)>) {
+        self.nodesets = nodesets;
+    }
+    /// returns the `.options`, `.temp` and `.nodeset` lines for the netlist
+    pub fn definitions(&self) -> String {
+        let mut ret = String::new();
+        if !self.options.is_empty() {
+            ret.push_str(&format!(".options {}\n", options_line(&self.options)));
+        }
+        if !self.temp.trim().is_empty() {
+            ret.push_str(&format!(".temp {}\n", self.temp.trim()));
+        }
+        if !self.nodesets.is_empty() {
+            ret.push_str(".nodeset");
+            for (net, v) in &self.nodesets {
+                ret.push_str(&format!(" v({})={}", net, v));
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+}
+
+/// returns the options as `name=value` entries separated by spaces
+fn options_line(options: &[(String, String)]) -> String {
+    options
+        .iter()
+        .map(|(n, v)| {
+            if v.is_empty() {
+                n.clone()
+            } else {
+                format!("{}={}", n, v)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Display for SimOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", options_line(&self.options))?;
+        if !self.nodesets.is_empty() {
+            if !self.options.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "nodeset of {} nets", self.nodesets.len())?;
+        }
+        Ok(())
+    }
+}